@@ -37,7 +37,46 @@ common-menu-copy = Copy
 common-menu-paste = Paste
 common-menu-undo = Undo
 common-menu-redo = Redo
+common-menu-find = Find...
+common-menu-find-next = Find Next
+common-menu-find-previous = Find Previous
+common-menu-goto-line = Go to Line...
+
+# find & replace bar
+find-case-sensitive = Match Case
+find-regex = Regex
+find-whole-words = Whole Words
+find-previous = Previous
+find-next = Next
+find-close = Close
+replace-next = Replace
+replace-all = Replace All
+
+# go to line bar
+goto-line-go = Go
+goto-line-close = Close
 
 # common 'View' menu items
 common-menu-view-menu = View
 common-menu-themes-menu = Themes
+common-menu-languages-menu = Language
+common-menu-plugins = Plugins
+common-menu-event-log = Event Log
+common-menu-line-numbers = Line Numbers
+common-menu-line-numbers-absolute = Absolute
+common-menu-line-numbers-relative = Relative
+common-menu-line-numbers-hidden = Hidden
+common-menu-word-wrap = Word Wrap
+
+# plugins tool window
+plugins-title = Plugins
+plugins-empty = No plugins
+plugins-running = running
+plugins-stopped = stopped
+plugins-start = Start
+plugins-stop = Stop
+
+# event log tool window
+event-log-title = Event Log
+event-log-empty = No events
+event-log-clear = Clear
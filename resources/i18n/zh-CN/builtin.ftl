@@ -37,7 +37,46 @@ common-menu-copy = 复制
 common-menu-paste = 粘贴
 common-menu-undo = 撤消
 common-menu-redo = 重做
+common-menu-find = 查找...
+common-menu-find-next = 查找下一个
+common-menu-find-previous = 查找上一个
+common-menu-goto-line = 跳转到行...
+
+# find & replace bar
+find-case-sensitive = 区分大小写
+find-regex = 正则表达式
+find-whole-words = 全字匹配
+find-previous = 上一个
+find-next = 下一个
+find-close = 关闭
+replace-next = 替换
+replace-all = 全部替换
+
+# go to line bar
+goto-line-go = 跳转
+goto-line-close = 关闭
 
 # common 'View' menu items
 common-menu-view-menu = View
 common-menu-themes-menu = Themes
+common-menu-languages-menu = 语言
+common-menu-plugins = 插件
+common-menu-event-log = 事件日志
+common-menu-line-numbers = 行号
+common-menu-line-numbers-absolute = 绝对行号
+common-menu-line-numbers-relative = 相对行号
+common-menu-line-numbers-hidden = 隐藏
+common-menu-word-wrap = 自动换行
+
+# plugins tool window
+plugins-title = 插件
+plugins-empty = 没有插件
+plugins-running = 运行中
+plugins-stopped = 已停止
+plugins-start = 启动
+plugins-stop = 停止
+
+# event log tool window
+event-log-title = 事件日志
+event-log-empty = 没有事件
+event-log-clear = 清空
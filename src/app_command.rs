@@ -1,6 +1,8 @@
 pub mod print_command {
     use crate::model::file_tree::FileEntry;
+    use crate::model::gutter::LineNumbers;
     use crate::rpc::client::RpcOperations;
+    use crate::rpc::structs::PluginCommand;
     use crate::AvailableThemes;
     use druid::Selector;
 
@@ -13,4 +15,28 @@ pub mod print_command {
     pub const XI_EVENT: Selector<RpcOperations> = Selector::new("print.xi-event");
     pub const LIST_THEMES: Selector<AvailableThemes> = Selector::new("print.xi-themes");
     pub const SET_THEME: Selector<String> = Selector::new("print.set-theme");
+    pub const SET_LANGUAGE: Selector<String> = Selector::new("print.set-language");
+
+    pub const SHOW_FIND: Selector = Selector::new("print.show-find");
+    pub const HIDE_FIND: Selector = Selector::new("print.hide-find");
+    pub const FIND_NEXT: Selector = Selector::new("print.find-next");
+    pub const FIND_PREVIOUS: Selector = Selector::new("print.find-previous");
+    pub const REPLACE_NEXT: Selector = Selector::new("print.replace-next");
+    pub const REPLACE_ALL: Selector = Selector::new("print.replace-all");
+
+    pub const TOGGLE_PLUGINS: Selector = Selector::new("print.toggle-plugins");
+    pub const START_PLUGIN: Selector<String> = Selector::new("print.start-plugin");
+    pub const STOP_PLUGIN: Selector<String> = Selector::new("print.stop-plugin");
+    /// The plugin and one of its `update_cmds` commands.
+    pub const RUN_PLUGIN_CMD: Selector<(String, PluginCommand)> =
+        Selector::new("print.run-plugin-cmd");
+
+    pub const TOGGLE_EVENT_LOG: Selector = Selector::new("print.toggle-event-log");
+    pub const SET_LINE_NUMBERS: Selector<LineNumbers> = Selector::new("print.set-line-numbers");
+    pub const TOGGLE_WORD_WRAP: Selector = Selector::new("print.toggle-word-wrap");
+    pub const SHOW_GOTO_LINE: Selector = Selector::new("print.show-goto-line");
+    pub const HIDE_GOTO_LINE: Selector = Selector::new("print.hide-goto-line");
+    /// A logical line number, counted from 1.
+    pub const GOTO_LINE: Selector<u64> = Selector::new("print.goto-line");
+    pub const SET_CLIPBOARD: Selector<String> = Selector::new("print.set-clipboard");
 }
@@ -1,10 +1,12 @@
 use crate::app_command::print_command;
 use crate::app_state::{AppState, Workspace};
 use crate::components::modal_host::ModalHost;
+use crate::model::notification::Level;
 use druid::widget::{Flex, Label};
-use druid::{AppDelegate, Command, DelegateCtx, Env, FileInfo, Handled, Target, Widget, WidgetExt};
-use std::fs::OpenOptions;
-use std::io::Write;
+use druid::{
+    AppDelegate, Application, Command, DelegateCtx, Env, FileInfo, Handled, Target, Widget,
+    WidgetExt,
+};
 use std::path::PathBuf;
 
 #[derive(Debug, Default)]
@@ -38,6 +40,64 @@ impl AppDelegate<AppState> for Delegate {
         }  else if let Some(theme) = cmd.get(print_command::SET_THEME) {
             data.set_theme(theme);
             return Handled::Yes;
+        } else if let Some(language) = cmd.get(print_command::SET_LANGUAGE) {
+            data.set_language(language);
+            return Handled::Yes;
+        } else if cmd.is(print_command::SHOW_FIND) {
+            data.show_find();
+            return Handled::Yes;
+        } else if cmd.is(print_command::HIDE_FIND) {
+            data.hide_find();
+            return Handled::Yes;
+        } else if cmd.is(print_command::FIND_NEXT) {
+            data.find_next(false);
+            return Handled::Yes;
+        } else if cmd.is(print_command::FIND_PREVIOUS) {
+            data.find_next(true);
+            return Handled::Yes;
+        } else if cmd.is(print_command::REPLACE_NEXT) {
+            data.replace(false);
+            return Handled::Yes;
+        } else if cmd.is(print_command::REPLACE_ALL) {
+            data.replace(true);
+            return Handled::Yes;
+        } else if cmd.is(print_command::TOGGLE_PLUGINS) {
+            data.params.show_plugins = !data.params.show_plugins;
+            data.save_global_config();
+            return Handled::Yes;
+        } else if let Some(name) = cmd.get(print_command::START_PLUGIN) {
+            data.start_plugin(name);
+            return Handled::Yes;
+        } else if let Some(name) = cmd.get(print_command::STOP_PLUGIN) {
+            data.stop_plugin(name);
+            return Handled::Yes;
+        } else if let Some((name, plugin_cmd)) = cmd.get(print_command::RUN_PLUGIN_CMD) {
+            data.run_plugin_cmd(name, plugin_cmd);
+            return Handled::Yes;
+        } else if cmd.is(print_command::TOGGLE_EVENT_LOG) {
+            data.params.show_event_log = !data.params.show_event_log;
+            data.save_global_config();
+            return Handled::Yes;
+        } else if let Some(line_numbers) = cmd.get(print_command::SET_LINE_NUMBERS) {
+            data.params.line_numbers = *line_numbers;
+            data.save_global_config();
+            return Handled::Yes;
+        } else if cmd.is(print_command::TOGGLE_WORD_WRAP) {
+            data.toggle_word_wrap();
+            return Handled::Yes;
+        } else if cmd.is(print_command::SHOW_GOTO_LINE) {
+            data.goto_line.visible = true;
+            data.goto_line.query.clear();
+            return Handled::Yes;
+        } else if cmd.is(print_command::HIDE_GOTO_LINE) {
+            data.goto_line.visible = false;
+            return Handled::Yes;
+        } else if let Some(line_num) = cmd.get(print_command::GOTO_LINE) {
+            data.goto_line(*line_num);
+            return Handled::Yes;
+        } else if let Some(text) = cmd.get(print_command::SET_CLIPBOARD) {
+            Application::global().clipboard().put_string(text);
+            return Handled::Yes;
         }
 
         Handled::No
@@ -64,39 +124,13 @@ impl Delegate {
             }
         };
 
-        log::info!("under type: {:?}", info);
+        let message = format!("Unsupported file type: {}", info.path().display());
+        state.notify(Level::Warning, message);
         return Handled::No;
     }
 
     fn save_file(data: &mut AppState) -> Handled {
-        let file_path;
-        match &data.current_file {
-            None => return Handled::Yes,
-            Some(path) => {
-                file_path = path;
-            }
-        };
-
-        let buf = file_path.to_path_buf();
-
-        if data.workspace.input_text == data.workspace.origin_text {
-            return Handled::Yes;
-        }
-
-        let mut ifile = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(true)
-            .open(&buf)
-            .expect("unable to open file");
-
-        let result = ifile.write_all(data.text().as_bytes());
-
-        match result {
-            Ok(_) => log::info!("save file: {:?}", buf),
-            Err(e) => log::info!("Failed to write data: {}", { e }),
-        }
-
+        data.save();
         return Handled::Yes;
     }
 
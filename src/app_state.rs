@@ -4,16 +4,25 @@ use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
-use druid::{Data, DelegateCtx, Lens};
+use druid::{Command, Data, DelegateCtx, Lens};
 use serde::{Deserialize, Serialize};
 
 use crate::app_command::print_command;
-use crate::linecache::LineCache;
+use crate::linecache;
 use crate::model::file_tree::FileEntry;
+use crate::model::find::FindState;
+use crate::model::goto_line::GotoLineState;
+use crate::model::gutter::LineNumbers;
+use crate::model::notification::{Level, Notifications};
+use crate::model::view::{ViewCore, ViewState};
 use crate::rpc::client::{Client, RpcOperations};
+use crate::rpc::structs::PluginCommand;
+use crate::rpc::transport::TransportConfig;
 use crate::support::directory;
+use crate::support::language;
+use crate::support::text_measure::TextMeasure;
 use crate::theme::u32_from_color;
-use crate::{AvailableThemes, Style, ThemeSettings};
+use crate::{AvailableThemes, ConfigChanges, EditCommand, ModifySelection, Style, ThemeSettings};
 use log::*;
 use std::collections::HashMap;
 
@@ -38,19 +47,40 @@ pub struct AppState {
     #[serde(skip_serializing, skip_deserializing)]
     pub themes: Vec<String>,
 
+    #[data(ignore)]
+    #[serde(skip_serializing, skip_deserializing)]
+    pub languages: Vec<String>,
+
+    #[data(ignore)]
+    #[serde(skip_serializing, skip_deserializing)]
+    pub config: ConfigChanges,
+
     pub params: Params,
 
+    #[data(ignore)]
+    #[serde(default)]
+    pub transport: TransportConfig,
+
+    /// Record all xi-core traffic to `~/.print/recordings`.
+    #[data(ignore)]
+    #[serde(default)]
+    pub record_rpc: bool,
+
     #[serde(skip_serializing, skip_deserializing)]
     pub entry: FileEntry,
 
     #[serde(skip_serializing, skip_deserializing)]
     pub core: Arc<Mutex<Client>>,
     #[serde(skip_serializing, skip_deserializing)]
-    pub view: Arc<Mutex<ViewCore>>,
+    pub views: ViewCore,
+
+    #[serde(skip_serializing, skip_deserializing)]
+    pub find: FindState,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub goto_line: GotoLineState,
 
-    #[serde(default)]
     #[serde(skip_serializing, skip_deserializing)]
-    pub view_id: usize,
+    pub notifications: Notifications,
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub current_file: Option<Arc<Path>>,
@@ -61,34 +91,6 @@ pub struct AppState {
     pub last_dir: Option<Arc<Path>>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Data, Lens, Debug)]
-pub struct ViewState {
-    id: usize,
-    filename: Option<String>,
-}
-
-#[derive(Serialize, Deserialize, Clone, Lens, Debug)]
-pub struct ViewCore {
-    focused: Option<String>,
-    views: HashMap<String, ViewState>,
-}
-
-impl Data for ViewCore {
-    // todo: add others compare for data
-    fn same(&self, other: &Self) -> bool {
-        self.focused == other.focused && self.views.len() == other.views.len()
-    }
-}
-
-impl Default for ViewCore {
-    fn default() -> Self {
-        ViewCore {
-            focused: None,
-            views: Default::default(),
-        }
-    }
-}
-
 impl Default for AppState {
     fn default() -> Self {
         Self {
@@ -98,14 +100,20 @@ impl Default for AppState {
             theme_name: "".to_string(),
             styles: Default::default(),
             themes: vec![],
+            languages: vec![],
+            config: Default::default(),
             params: Default::default(),
+            transport: Default::default(),
+            record_rpc: false,
             entry: Default::default(),
             core: Arc::new(Mutex::new(Default::default())),
-            view: Arc::new(Mutex::new(Default::default())),
+            views: Default::default(),
+            find: Default::default(),
+            goto_line: Default::default(),
+            notifications: Default::default(),
             current_file: None,
             current_dir: None,
             last_dir: None,
-            view_id: 0,
         }
     }
 }
@@ -114,10 +122,12 @@ impl AppState {
     pub fn open_file(&mut self, path: impl Into<Option<PathBuf>>) {
         let path: Option<Arc<Path>> = path.into().map(Into::into);
 
+        let file_path = path.as_ref().unwrap();
         let mut file_content: Vec<u8> = Vec::new();
-        let mut file = File::open(&path.as_ref().unwrap()).expect("Unable to open file");
-        if let Err(err) = file.read_to_end(&mut file_content) {
-            log::error!("open file error: {:?}", err);
+        let result = File::open(file_path).and_then(|mut file| file.read_to_end(&mut file_content));
+        if let Err(err) = result {
+            let message = format!("Failed to open {}: {}", file_path.display(), err);
+            self.notify(Level::Error, message);
             return;
         };
 
@@ -136,25 +146,7 @@ impl AppState {
     }
 
     fn req_new_view(&self, filename: String) {
-        let view = self.view.clone();
-        let mut core = self.core.lock().unwrap();
-        core.new_view(filename.clone(), move |res| {
-            if let Ok(val) = res {
-                let id: Option<String> = serde_json::from_value(val).unwrap();
-                if let Some(view_id) = id {
-                    let mut state = view.lock().unwrap();
-
-                    state.focused = Some(view_id.clone());
-                    state.views.insert(
-                        view_id.clone(),
-                        ViewState {
-                            id: 0,
-                            filename: Option::from(filename),
-                        },
-                    );
-                }
-            }
-        });
+        self.core.lock().unwrap().open_view(filename);
     }
 
     pub fn reload_dir(&mut self) {
@@ -182,8 +174,20 @@ impl AppState {
         self.save_global_config();
     }
 
-    pub fn text(&mut self) -> String {
-        return self.workspace.input_text.clone();
+    /// Saves the focused view. xi-core writes the file and reports failures
+    /// with an `alert`.
+    pub fn save(&mut self) {
+        let (view_id, file_path) = match self.views.focused() {
+            Some(view) if !view.pristine => (view.id, view.file_path.clone()),
+            _ => return,
+        };
+        match file_path {
+            Some(file_path) => {
+                info!("save file: {}", file_path);
+                self.core.lock().unwrap().save(&view_id, &file_path);
+            }
+            None => self.notify(Level::Warning, "The view has no file to save to"),
+        }
     }
 
     // todo: add save project config
@@ -193,7 +197,21 @@ impl AppState {
         current_state.workspace = Default::default();
         current_state.entry = Default::default();
 
-        directory::save_config(&current_state);
+        if let Err(err) = directory::save_config(&current_state) {
+            self.notify(Level::Error, format!("Failed to save settings: {}", err));
+        }
+    }
+
+    /// Logs `message` and adds it to the event log, warnings and errors
+    /// also pop up a toast.
+    pub fn notify(&mut self, level: Level, message: impl Into<String>) {
+        let message = message.into();
+        match level {
+            Level::Info => info!("{}", message),
+            Level::Warning => warn!("{}", message),
+            Level::Error => error!("{}", message),
+        }
+        self.notifications.push(level, message);
     }
 
     pub fn setup_workspace(&mut self) {
@@ -210,28 +228,72 @@ impl AppState {
 // for xipart
 impl AppState {
     pub fn handle_event(&mut self, op: &RpcOperations, ctx: &mut DelegateCtx) {
+        for cmd in self.apply_event(op) {
+            ctx.submit_command(cmd);
+        }
+    }
+
+    /// Applies an xi-core event to the state and returns the commands it
+    /// triggers, so it can be driven without a `DelegateCtx`.
+    pub fn apply_event(&mut self, op: &RpcOperations) -> Vec<Command> {
+        let mut commands = vec![];
         let mut core = self.core.lock().unwrap();
-        let view = self.view.lock().unwrap();
         match op {
             RpcOperations::AvailableThemes(themes) => {
-                ctx.submit_command(print_command::LIST_THEMES.with(themes.clone()));
+                commands.push(print_command::LIST_THEMES.with(themes.clone()));
+            }
+            RpcOperations::AvailablePlugins(available) => {
+                let view = self.views.entry(available.view_id);
+                view.plugins.set_available(&available.plugins);
             }
-            RpcOperations::AvailablePlugins(_plugins) => {}
-            RpcOperations::AvailableLanguages(_langs) => {
-                if let Some(view_id) = view.focused.as_ref() {
-                    core.send_notification(
-                        "set_language",
-                        &json!({ "view_id": view_id, "language_id": "JavaScript" }),
-                    );
-                } else {
-                    core.send_notification(
-                        "set_language",
-                        &json!({ "view_id": "view-id-1", "language_id": "JavaScript" }),
-                    );
+            RpcOperations::PluginStarted(started) => {
+                let view = self.views.entry(started.view_id);
+                view.plugins.set_running(&started.plugin, true);
+            }
+            RpcOperations::PluginStopped(stopped) => {
+                let view = self.views.entry(stopped.view_id);
+                view.plugins.set_running(&stopped.plugin, false);
+            }
+            RpcOperations::UpdateCmds(update) => {
+                let view = self.views.entry(update.view_id);
+                view.plugins.set_cmds(&update.plugin, &update.cmds);
+            }
+            RpcOperations::AvailableLanguages(langs) => {
+                self.languages = langs.languages.clone();
+                for view in self.views.views.values() {
+                    AppState::detect_language(&mut core, view, &self.languages);
                 }
             }
+            RpcOperations::LanguageChanged(changed) => {
+                self.views.entry(changed.view_id).language = Some(changed.language_id.clone());
+            }
+            RpcOperations::ViewOpened { view_id, file_path } => {
+                let view = self.views.open(*view_id, file_path.clone());
+                AppState::detect_language(&mut core, view, &self.languages);
+            }
             RpcOperations::Update(update) => {
-                self.workspace.line_cache.update(update.clone());
+                let focused = self.views.focused == Some(update.view_id);
+                let view = self.views.entry(update.view_id);
+                view.line_cache.set_budget(self.params.line_cache_budget());
+                view.update(update.clone());
+                if let Some(line) = view.correct_goto() {
+                    core.edit(&update.view_id, EditCommand::GotoLine { line });
+                }
+                if focused {
+                    if let Some(text) = view.text() {
+                        self.workspace.input_text = text;
+                    }
+                }
+            }
+            RpcOperations::ScrollTo(scroll_to) => {
+                let view = self.views.entry(scroll_to.view_id);
+                view.cursor = (scroll_to.line, scroll_to.column);
+            }
+            RpcOperations::FindStatus(status) => {
+                self.views.entry(status.view_id).find_status = Some(status.clone());
+            }
+            RpcOperations::ReplaceStatus(status) => {
+                self.views.entry(status.view_id).replace_status = Some(status.clone());
             }
             RpcOperations::DefStyle(params) => {
                 self.styles.insert(params.id as usize, params.clone());
@@ -251,12 +313,112 @@ impl AppState {
 
                 // todo: update view;
                 self.styles.insert(0, selection_style);
+
+                // xi-core reserves style 1 for find matches
+                let find_style = Style {
+                    id: 1,
+                    fg_color: param.theme.find_highlight_foreground.map(u32_from_color),
+                    bg_color: param.theme.find_highlight.map(u32_from_color),
+                    weight: None,
+                    italic: None,
+                    underline: None,
+                };
+                self.styles.insert(1, find_style);
+            }
+            RpcOperations::ConfigChanged(config) => {
+                self.views
+                    .entry(config.view_id)
+                    .config
+                    .apply(&config.changes);
+                // measure_width carries no view id, so measuring uses the latest config
+                self.config.apply(&config.changes);
             }
             RpcOperations::MeasureWidth((id, measure_width)) => {
-                info!("id: {:?}, width: {:?}", id, measure_width);
+                let measure = TextMeasure::new(&self.config);
+                match measure.measure(measure_width, &self.styles) {
+                    Ok(widths) => core.send_response(*id, Ok(json!(widths))),
+                    Err(err) => {
+                        error!("measure width error: {:?}", err);
+                        core.send_response(*id, Err(json!({ "message": err.to_string() })));
+                    }
+                }
+            }
+            RpcOperations::Copied(text) => {
+                commands.push(print_command::SET_CLIPBOARD.with(text.clone()));
+            }
+            RpcOperations::Alert(alert) => {
+                warn!("xi-core alert: {}", alert.msg);
+                self.notifications.push(Level::Warning, alert.msg.clone());
+            }
+            RpcOperations::Unknown { method, params } => {
+                warn!("unhandled xi-core method `{}`: {}", method, params);
+            }
+            RpcOperations::Error(err) => {
+                error!("xi-core: {}", err);
+                self.notifications.push(Level::Error, err.to_string());
             }
             _ => {}
         }
+        commands
+    }
+
+    /// Tells xi-core which language the file looks like, when that is not
+    /// what it already uses.
+    fn detect_language(core: &mut Client, view: &ViewState, languages: &[String]) {
+        let path = match view.file_path.as_ref() {
+            Some(path) => Path::new(path),
+            None => return,
+        };
+        if languages.is_empty() {
+            return;
+        }
+
+        let text = language::read_ends(path).unwrap_or_default();
+        let detected = language::detect(path, &text, languages);
+        if detected.is_some() && detected != view.language {
+            core.send_notification(
+                "set_language",
+                &json!({ "view_id": view.id, "language_id": detected }),
+            );
+        }
+    }
+
+    pub fn set_language(&mut self, language: &String) {
+        if let Some(view_id) = self.views.focused {
+            self.core.lock().unwrap().send_notification(
+                "set_language",
+                &json!({ "view_id": view_id, "language_id": language }),
+            );
+        }
+    }
+
+    /// Turns soft wrap on or off for the focused view alone. Lines wrap at
+    /// `params.wrap_column`, or at the width of the editor while that's 0.
+    pub fn toggle_word_wrap(&mut self) {
+        let view = match self.views.focused() {
+            Some(view) => view,
+            None => return,
+        };
+        let changes = if view.config.wraps() {
+            json!({ "word_wrap": false, "wrap_width": 0 })
+        } else if self.params.wrap_column > 0 {
+            json!({ "word_wrap": false, "wrap_width": self.params.wrap_column })
+        } else {
+            json!({ "word_wrap": true, "wrap_width": 0 })
+        };
+        self.core
+            .lock()
+            .unwrap()
+            .modify_view_config(&view.id, &changes);
+    }
+
+    /// Puts the caret at the start of logical line `line_num`, counted
+    /// from 1. xi-core counts visual lines, which differ once lines wrap.
+    pub fn goto_line(&mut self, line_num: u64) {
+        if let Some(view_id) = self.views.focused {
+            let line = self.views.entry(view_id).goto_row(line_num);
+            self.edit_focused(EditCommand::GotoLine { line });
+        }
     }
 
     pub fn set_theme(&mut self, theme: &String) {
@@ -272,16 +434,106 @@ impl AppState {
     }
 }
 
+// for find & replace
+impl AppState {
+    pub fn show_find(&mut self) {
+        self.find.visible = true;
+        self.edit_focused(EditCommand::HighlightFind { visible: true });
+        if !self.find.query.is_empty() {
+            self.find();
+        }
+    }
+
+    pub fn hide_find(&mut self) {
+        self.find.visible = false;
+        self.edit_focused(EditCommand::HighlightFind { visible: false });
+    }
+
+    pub fn find(&mut self) {
+        let cmd = self.find.find_command();
+        self.edit_focused(cmd);
+    }
+
+    pub fn find_next(&mut self, reverse: bool) {
+        let (wrap_around, allow_same, modify_selection) = (true, false, ModifySelection::Set);
+        let cmd = if reverse {
+            EditCommand::FindPrevious {
+                wrap_around,
+                allow_same,
+                modify_selection,
+            }
+        } else {
+            EditCommand::FindNext {
+                wrap_around,
+                allow_same,
+                modify_selection,
+            }
+        };
+        self.edit_focused(cmd);
+    }
+
+    pub fn replace(&mut self, all: bool) {
+        self.edit_focused(EditCommand::Replace {
+            chars: self.find.replacement.clone(),
+            preserve_case: false,
+        });
+        let cmd = if all {
+            EditCommand::ReplaceAll
+        } else {
+            EditCommand::ReplaceNext
+        };
+        self.edit_focused(cmd);
+    }
+
+    pub fn edit_focused(&self, cmd: EditCommand) {
+        if let Some(view_id) = self.views.focused {
+            self.core.lock().unwrap().edit(&view_id, cmd);
+        }
+    }
+
+    /// Puts the focused view's selection on the clipboard, deleting it
+    /// from the view when `cut`.
+    pub fn copy_focused(&self, cut: bool) {
+        if let Some(view_id) = self.views.focused {
+            let core = self.core.lock().unwrap();
+            if cut {
+                core.cut(&view_id);
+            } else {
+                core.copy(&view_id);
+            }
+        }
+    }
+}
+
+// for plugins
+impl AppState {
+    pub fn start_plugin(&mut self, name: &str) {
+        if let Some(view_id) = self.views.focused {
+            self.core.lock().unwrap().start_plugin(&view_id, name);
+        }
+    }
+
+    pub fn stop_plugin(&mut self, name: &str) {
+        if let Some(view_id) = self.views.focused {
+            self.core.lock().unwrap().stop_plugin(&view_id, name);
+        }
+    }
+
+    pub fn run_plugin_cmd(&mut self, name: &str, cmd: &PluginCommand) {
+        if let Some(view_id) = self.views.focused {
+            let mut core = self.core.lock().unwrap();
+            core.plugin_rpc(&view_id, name, &cmd.rpc_cmd);
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Data, Lens, Debug)]
 pub struct Workspace {
     pub project: String,
-    pub origin_text: String,
+    /// A snapshot of the focused view, while xi-core has sent all of it.
     pub input_text: String,
     pub char_count: usize,
 
-    #[serde(skip_serializing, skip_deserializing)]
-    pub line_cache: LineCache,
-
     #[serde(default)]
     pub dir: Arc<PathBuf>,
 
@@ -311,10 +563,8 @@ impl Default for Workspace {
     fn default() -> Self {
         Workspace {
             project: "".to_string(),
-            origin_text: "".to_string(),
             input_text: "".to_string(),
             char_count: 0,
-            line_cache: Default::default(),
             dir: Default::default(),
             current_file: Default::default(),
         }
@@ -324,12 +574,360 @@ impl Default for Workspace {
 #[derive(Serialize, Deserialize, Clone, Data, Lens, Debug)]
 pub struct Params {
     pub debug_layout: bool,
+    #[serde(default)]
+    pub show_plugins: bool,
+    #[serde(default)]
+    pub show_event_log: bool,
+    #[serde(default)]
+    pub line_numbers: LineNumbers,
+    /// How many megabytes the cached lines of each view may take.
+    #[serde(default = "Params::default_line_cache_mb")]
+    pub line_cache_mb: usize,
+    /// The column soft wrap wraps at, 0 for the width of the editor.
+    #[serde(default)]
+    pub wrap_column: u64,
 }
 
 impl Default for Params {
     fn default() -> Self {
         Self {
             debug_layout: false,
+            show_plugins: false,
+            show_event_log: false,
+            line_numbers: LineNumbers::default(),
+            line_cache_mb: Params::default_line_cache_mb(),
+            wrap_column: 0,
+        }
+    }
+}
+
+impl Params {
+    fn default_line_cache_mb() -> usize {
+        linecache::DEFAULT_BUDGET / (1024 * 1024)
+    }
+
+    pub fn line_cache_budget(&self) -> usize {
+        self.line_cache_mb * 1024 * 1024
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::fake_core::{FakeCore, Received};
+    use crate::rpc::message::Message;
+    use crate::ViewId;
+    use serde_json::Value;
+    use std::thread;
+    use std::time::Duration;
+
+    const WAIT: Duration = Duration::from_secs(5);
+
+    fn update(view_id: &str, text: &str) -> Value {
+        json!({
+            "view_id": view_id,
+            "update": {
+                "ops": [{ "op": "ins", "n": 1, "lines": [{ "text": text, "ln": 1 }] }],
+                "pristine": true
+            }
+        })
+    }
+
+    /// Waits until the fake core got `count` messages from the client.
+    fn wait_for(received: &Received, count: usize) {
+        for _ in 0..500 {
+            if received.lock().unwrap().len() >= count {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn line(state: &AppState, view_id: ViewId) -> String {
+        let view = state.views.get(view_id).unwrap();
+        view.line_cache.get_line(0).unwrap().text.clone()
+    }
+
+    #[test]
+    fn xi_events_update_state() {
+        let core = FakeCore::new()
+            .notify("available_themes", json!({ "themes": ["InspiredGitHub"] }))
+            .notify("available_languages", json!({ "languages": ["Rust"] }))
+            .notify("update", update("view-id-1", "hello\n"));
+        let (client, operations) = Client::with_transport(Box::new(core)).unwrap();
+
+        let mut state = AppState::default();
+        state.core = Arc::new(Mutex::new(client));
+
+        let commands = state.apply_event(&operations.recv_timeout(WAIT).unwrap());
+        assert_eq!(1, commands.len());
+        assert!(commands[0].is(print_command::LIST_THEMES));
+
+        let commands = state.apply_event(&operations.recv_timeout(WAIT).unwrap());
+        assert!(commands.is_empty());
+        assert_eq!(vec!["Rust".to_string()], state.languages);
+
+        state.apply_event(&operations.recv_timeout(WAIT).unwrap());
+        assert_eq!("hello\n", line(&state, ViewId(1)));
+    }
+
+    #[test]
+    fn detected_language_is_sent_for_new_views() {
+        let core = FakeCore::new()
+            .notify(
+                "available_languages",
+                json!({ "languages": ["Plain Text", "Rust"] }),
+            )
+            .on_request("new_view", json!("view-id-1"))
+            .then_notify(
+                "language_changed",
+                json!({ "view_id": "view-id-1", "language_id": "Plain Text" }),
+            );
+        let received = core.received();
+        let (client, operations) = Client::with_transport(Box::new(core)).unwrap();
+
+        let mut state = AppState::default();
+        state.core = Arc::new(Mutex::new(client));
+        state.apply_event(&operations.recv_timeout(WAIT).unwrap());
+
+        state.req_new_view("does/not/exist.rs".to_string());
+        for _ in 0..2 {
+            state.apply_event(&operations.recv_timeout(WAIT).unwrap());
+        }
+
+        wait_for(&received, 2);
+        let received = received.lock().unwrap();
+        match &received[1] {
+            Message::Notification(n) => {
+                assert_eq!("set_language", n.method);
+                assert_eq!(
+                    json!({ "view_id": "view-id-1", "language_id": "Rust" }),
+                    n.params
+                );
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn events_are_routed_by_view_id() {
+        let core = FakeCore::new()
+            .on_request("new_view", json!("view-id-1"))
+            .then_notify("update", update("view-id-1", "one"))
+            .on_request("new_view", json!("view-id-2"))
+            .then_notify("update", update("view-id-2", "two"))
+            .then_notify(
+                "scroll_to",
+                json!({ "view_id": "view-id-2", "line": 0, "col": 3 }),
+            );
+        let (client, operations) = Client::with_transport(Box::new(core)).unwrap();
+
+        let mut state = AppState::default();
+        state.core = Arc::new(Mutex::new(client));
+
+        state.req_new_view("one.rs".to_string());
+        for _ in 0..2 {
+            state.apply_event(&operations.recv_timeout(WAIT).unwrap());
+        }
+        state.req_new_view("two.rs".to_string());
+        for _ in 0..3 {
+            state.apply_event(&operations.recv_timeout(WAIT).unwrap());
+        }
+
+        assert_eq!(Some(ViewId(2)), state.views.focused);
+        assert_eq!("one", line(&state, ViewId(1)));
+        assert_eq!("two", line(&state, ViewId(2)));
+
+        let two = state.views.get(ViewId(2)).unwrap();
+        assert_eq!(Some("two.rs".to_string()), two.file_path);
+        assert_eq!((0, 3), two.cursor);
+    }
+
+    #[test]
+    fn find_bar_sends_edits_to_focused_view() {
+        let core = FakeCore::new();
+        let received = core.received();
+        let (client, _operations) = Client::with_transport(Box::new(core)).unwrap();
+
+        let mut state = AppState::default();
+        state.core = Arc::new(Mutex::new(client));
+        state.apply_event(&RpcOperations::ViewOpened {
+            view_id: ViewId(4),
+            file_path: "a.rs".to_string(),
+        });
+
+        state.find.query = "foo".to_string();
+        state.find.whole_words = true;
+        state.show_find();
+        state.find_next(true);
+        state.find.replacement = "bar".to_string();
+        state.replace(true);
+
+        let expected = vec![
+            "highlight_find",
+            "find",
+            "find_previous",
+            "replace",
+            "replace_all",
+        ];
+        wait_for(&received, expected.len());
+        let received = received.lock().unwrap();
+        let edits: Vec<&Value> = received
+            .iter()
+            .map(|msg| match msg {
+                Message::Notification(n) if n.method == "edit" => &n.params,
+                other => panic!("unexpected message: {:?}", other),
+            })
+            .collect();
+        let methods: Vec<&str> = edits
+            .iter()
+            .map(|params| params["method"].as_str().unwrap())
+            .collect();
+        assert_eq!(expected, methods);
+        assert_eq!(json!("view-id-4"), edits[1]["view_id"]);
+        assert_eq!(json!(true), edits[1]["params"]["whole_words"]);
+        assert_eq!(json!("bar"), edits[3]["params"]["chars"]);
+    }
+
+    #[test]
+    fn word_wrap_and_goto_line_follow_the_focused_view() {
+        let core = FakeCore::new();
+        let received = core.received();
+        let (client, _operations) = Client::with_transport(Box::new(core)).unwrap();
+
+        let mut state = AppState::default();
+        state.core = Arc::new(Mutex::new(client));
+        state.apply_event(&RpcOperations::ViewOpened {
+            view_id: ViewId(1),
+            file_path: "a.rs".to_string(),
+        });
+        // line 2 wraps over two rows
+        state.apply_event(&RpcOperations::Update(
+            serde_json::from_value(json!({
+                "view_id": "view-id-1",
+                "update": { "ops": [{ "op": "ins", "n": 4, "lines": [
+                    { "text": "one\n", "ln": 1 },
+                    { "text": "two ", "ln": 2 },
+                    { "text": "wrapped\n" },
+                    { "text": "three", "ln": 3 }
+                ] }], "pristine": true }
+            }))
+            .unwrap(),
+        ));
+
+        state.toggle_word_wrap();
+        state.views.entry(ViewId(1)).config.word_wrap = Some(true);
+        state.toggle_word_wrap();
+        state.params.wrap_column = 80;
+        state.views.entry(ViewId(1)).config.word_wrap = Some(false);
+        state.toggle_word_wrap();
+        state.goto_line(3);
+
+        wait_for(&received, 4);
+        let received = received.lock().unwrap();
+        let params: Vec<&Value> = received
+            .iter()
+            .map(|msg| match msg {
+                Message::Notification(n) => &n.params,
+                other => panic!("unexpected message: {:?}", other),
+            })
+            .collect();
+        let domain = json!({ "user_override": "view-id-1" });
+        assert_eq!(domain, params[0]["domain"]);
+        assert_eq!(
+            json!({ "word_wrap": true, "wrap_width": 0 }),
+            params[0]["changes"]
+        );
+        assert_eq!(
+            json!({ "word_wrap": false, "wrap_width": 0 }),
+            params[1]["changes"]
+        );
+        assert_eq!(
+            json!({ "word_wrap": false, "wrap_width": 80 }),
+            params[2]["changes"]
+        );
+        // the third line starts on the fourth row
+        assert_eq!(json!("goto_line"), params[3]["method"]);
+        assert_eq!(json!({ "line": 3 }), params[3]["params"]);
+    }
+
+    #[test]
+    fn plugin_events_are_tracked_per_view() {
+        let core = FakeCore::new()
+            .notify(
+                "available_plugins",
+                json!({ "view_id": "view-id-1", "plugins": [{ "name": "syntect", "running": true }] }),
+            )
+            .notify(
+                "update_cmds",
+                json!({ "view_id": "view-id-1", "plugin": "syntect", "cmds": [{
+                    "title": "Reload",
+                    "description": "Reloads the syntax definitions",
+                    "rpc_cmd": { "rpc_type": "notification", "method": "reload", "params": { "all": true } },
+                    "args": []
+                }] }),
+            )
+            .notify(
+                "plugin_stopped",
+                json!({ "view_id": "view-id-2", "plugin": "syntect" }),
+            );
+        let received = core.received();
+        let (client, operations) = Client::with_transport(Box::new(core)).unwrap();
+
+        let mut state = AppState::default();
+        state.core = Arc::new(Mutex::new(client));
+        for _ in 0..3 {
+            state.apply_event(&operations.recv_timeout(WAIT).unwrap());
+        }
+
+        let plugins = &state.views.get(ViewId(1)).unwrap().plugins.0;
+        assert!(plugins[0].running);
+        assert_eq!("Reload", plugins[0].cmds[0].title);
+        assert!(!state.views.get(ViewId(2)).unwrap().plugins.0[0].running);
+
+        let reload = plugins[0].cmds[0].clone();
+        state.views.focused = Some(ViewId(1));
+        state.run_plugin_cmd("syntect", &reload);
+        state.stop_plugin("syntect");
+        wait_for(&received, 2);
+        let received = received.lock().unwrap();
+        match &received[0] {
+            Message::Notification(n) => {
+                assert_eq!(json!("plugin_rpc"), n.params["command"]);
+                assert_eq!(json!("syntect"), n.params["receiver"]);
+                let rpc = json!({ "rpc_type": "notification", "method": "reload", "params": { "all": true } });
+                assert_eq!(rpc, n.params["rpc"]);
+            }
+            other => panic!("unexpected message: {:?}", other),
         }
+        match &received[1] {
+            Message::Notification(n) => {
+                assert_eq!("plugin", n.method);
+                assert_eq!(json!("stop"), n.params["command"]);
+                assert_eq!(json!("view-id-1"), n.params["view_id"]);
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn alerts_and_errors_are_notified() {
+        let core = FakeCore::new()
+            .notify("alert", json!({ "msg": "failed to save" }))
+            .notify("alert", json!({ "foo": 1 }));
+        let (client, operations) = Client::with_transport(Box::new(core)).unwrap();
+
+        let mut state = AppState::default();
+        state.core = Arc::new(Mutex::new(client));
+        for _ in 0..2 {
+            state.apply_event(&operations.recv_timeout(WAIT).unwrap());
+        }
+
+        let entries = &state.notifications.entries;
+        assert_eq!(2, entries.len());
+        assert_eq!(Level::Warning, entries[0].level);
+        assert_eq!("failed to save", entries[0].message);
+        assert_eq!(Level::Error, entries[1].level);
+        assert_eq!(2, state.notifications.toasts.len());
     }
 }
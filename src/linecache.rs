@@ -2,10 +2,13 @@
 // Copyright (C) 2019-2020 Rasmus Thomsen <oss@cogitri.dev>
 // SPDX-License-Identifier: MIT
 
-use crate::{rpc, OperationType, StyleDef, Update};
+use crate::{rpc, Annotation, AnnotationType, OperationType, StyleDef, Update};
 use druid::Data;
-use log::{error, trace};
+use log::trace;
 use std::cmp::min;
+use std::collections::BTreeMap;
+use std::mem;
+use std::sync::Arc;
 
 /// A Struct representing _one_ line which xi has sent us.
 /// # Fields:
@@ -32,69 +35,370 @@ impl From<rpc::Line> for Line {
     }
 }
 
-#[derive(Debug, Default, Clone)]
+/// Lines are kept in chunks of this many lines. Chunks without any valid
+/// line aren't allocated, and eviction drops whole chunks.
+const CHUNK_LINES: u64 = 256;
+
+/// How much memory the lines of one view may take before the ones furthest
+/// from what xi-core last sent are evicted.
+pub const DEFAULT_BUDGET: usize = 64 * 1024 * 1024;
+
+/// The bytes a line takes, roughly: the struct and its heap allocations.
+fn line_bytes(line: &Line) -> usize {
+    mem::size_of::<Line>()
+        + line.text.capacity()
+        + line.cursor.capacity() * mem::size_of::<u64>()
+        + line.styles.capacity() * mem::size_of::<StyleDef>()
+}
+
+#[derive(Debug, Clone)]
+struct Chunk {
+    lines: Vec<Option<Line>>,
+    bytes: usize,
+}
+
+impl Chunk {
+    fn new() -> Chunk {
+        Chunk {
+            lines: vec![None; CHUNK_LINES as usize],
+            bytes: CHUNK_LINES as usize * mem::size_of::<Option<Line>>(),
+        }
+    }
+}
+
+/// Lines being appended to, for a cache under construction. Chunks are
+/// shared with the clones druid makes of the cache, so they're only copied
+/// once they change.
+#[derive(Default)]
+struct Chunks {
+    height: u64,
+    chunks: BTreeMap<u64, Arc<Chunk>>,
+    bytes: usize,
+    /// The row each logical line with a cached first row starts on.
+    line_starts: BTreeMap<u64, u64>,
+}
+
+impl Chunks {
+    fn push_invalid(&mut self, n: u64) {
+        self.height += n;
+    }
+    fn push(&mut self, line: Line) {
+        let ix = self.height;
+        let key = ix / CHUNK_LINES;
+        if !self.chunks.contains_key(&key) {
+            let chunk = Chunk::new();
+            self.bytes += chunk.bytes;
+            self.chunks.insert(key, Arc::new(chunk));
+        }
+        if let Some(line_num) = line.line_num {
+            self.line_starts.insert(line_num, ix);
+        }
+        let bytes = line_bytes(&line);
+        let chunk = Arc::make_mut(self.chunks.get_mut(&key).unwrap());
+        chunk.lines[(ix % CHUNK_LINES) as usize] = Some(line);
+        chunk.bytes += bytes;
+        self.bytes += bytes;
+        self.height += 1;
+    }
+    /// Takes line `ix` out, leaving it invalid. From a chunk a clone of the
+    /// cache still shares, the line is copied instead of the whole chunk.
+    fn take(&mut self, ix: u64) -> Option<Line> {
+        let chunk = self.chunks.get_mut(&(ix / CHUNK_LINES))?;
+        let slot = (ix % CHUNK_LINES) as usize;
+        let chunk = match Arc::get_mut(chunk) {
+            Some(chunk) => chunk,
+            None => return chunk.lines[slot].clone(),
+        };
+        let line = chunk.lines[slot].take()?;
+        chunk.bytes -= line_bytes(&line);
+        self.bytes -= line_bytes(&line);
+        Some(line)
+    }
+    /// Moves chunk `key` of `old` over as it is, when it's copied whole, to
+    /// the start of a chunk, without being renumbered.
+    fn move_chunk(&mut self, old: &mut Chunks, key: u64, start: u64, end: u64, base: u64) -> bool {
+        let first = key * CHUNK_LINES;
+        if first < start || first + CHUNK_LINES > end {
+            return false;
+        }
+        let row = base + first - start;
+        if row % CHUNK_LINES != 0 {
+            return false;
+        }
+        let chunk = match old.chunks.remove(&key) {
+            Some(chunk) => chunk,
+            None => return false,
+        };
+        old.bytes -= chunk.bytes;
+        self.bytes += chunk.bytes;
+        for (ix, line) in chunk.lines.iter().enumerate() {
+            if let Some(line_num) = line.as_ref().and_then(|line| line.line_num) {
+                self.line_starts.insert(line_num, row + ix as u64);
+            }
+        }
+        self.chunks.insert(row / CHUNK_LINES, chunk);
+        true
+    }
+    /// Moves lines `start..start + n` of `old` over, walking only the chunks
+    /// `old` has so long runs of invalid lines are cheap. With `ln`, the
+    /// logical line the first of them belongs to now, they're renumbered,
+    /// and how is returned.
+    fn copy_from(
+        &mut self,
+        old: &mut Chunks,
+        start: u64,
+        n: u64,
+        ln: Option<u64>,
+    ) -> Option<LineShift> {
+        let base = self.height;
+        let end = start + n;
+        let mut moved: Option<(u64, u64)> = None;
+        let mut shift = None;
+        if n > 0 {
+            let keys: Vec<u64> = old
+                .chunks
+                .range(start / CHUNK_LINES..=(end - 1) / CHUNK_LINES)
+                .map(|(key, _)| *key)
+                .collect();
+            shift = ln.and_then(|ln| old.shift(&keys, start, end, ln));
+            for key in keys {
+                let renumbered = shift.map_or(false, |shift| shift != 0);
+                if !renumbered && self.move_chunk(old, key, start, end, base) {
+                    continue;
+                }
+                let first = (key * CHUNK_LINES).max(start);
+                let last = ((key + 1) * CHUNK_LINES).min(end);
+                for ix in first..last {
+                    if let Some(mut line) = old.take(ix) {
+                        if let Some(num) = line.line_num {
+                            moved = Some((moved.map_or(num, |(first, _)| first), num));
+                        }
+                        if let Some(shift) = shift {
+                            line.line_num = line.line_num.map(|num| (num as i64 + shift) as u64);
+                        }
+                        self.height = base + ix - start;
+                        self.push(line);
+                    }
+                }
+            }
+        }
+        self.height = base + n;
+        let (first, last) = moved?;
+        Some(LineShift {
+            first,
+            // the lines below are copied along, cached or not
+            last: Some(last).filter(|_| end < old.height),
+            delta: shift.filter(|shift| *shift != 0)?,
+        })
+    }
+    /// How much the line numbers of rows `start..end` change for the first
+    /// row to belong to logical line `ln`, going by the first numbered row.
+    /// The cached rows before it continue a wrapped line, the invalid ones
+    /// are counted as lines of their own.
+    fn shift(&self, keys: &[u64], start: u64, end: u64, ln: u64) -> Option<i64> {
+        let mut continuations = 0;
+        for key in keys {
+            let chunk = &self.chunks[key];
+            let first = (key * CHUNK_LINES).max(start);
+            let last = ((key + 1) * CHUNK_LINES).min(end);
+            for ix in first..last {
+                let line = match &chunk.lines[(ix % CHUNK_LINES) as usize] {
+                    Some(line) => line,
+                    None => continue,
+                };
+                match line.line_num {
+                    Some(num) => {
+                        let renumbered = if ix == start {
+                            ln
+                        } else {
+                            ln + (ix - start) - continuations
+                        };
+                        return Some(renumbered as i64 - num as i64);
+                    }
+                    None if ix > start => continuations += 1,
+                    None => {}
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Logical lines moved by an update, as told by the `ln` of the copy that
+/// carried them: `first` to `last` (inclusive), or to the end of the buffer
+/// without `last`, now `delta` lines further down.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LineShift {
+    pub first: u64,
+    pub last: Option<u64>,
+    pub delta: i64,
+}
+
+impl LineShift {
+    /// Where logical line `line_num` went, when it's one of the moved lines.
+    pub fn apply(&self, line_num: u64) -> Option<u64> {
+        if line_num < self.first || self.last.map_or(false, |last| line_num > last) {
+            return None;
+        }
+        Some((line_num as i64 + self.delta) as u64)
+    }
+
+    /// Whether logical line `line_num` is now one of the moved lines.
+    pub fn covers(&self, line_num: u64) -> bool {
+        let line_num = line_num as i64;
+        line_num >= self.first as i64 + self.delta
+            && self
+                .last
+                .map_or(true, |last| line_num <= last as i64 + self.delta)
+    }
+}
+
+/// The lines of a view that xi-core sent us, in chunks with a memory budget.
+/// Lines that were never sent, or were evicted, are invalid. Cloning shares
+/// the chunks, druid clones the app state all the time.
+#[derive(Debug, Clone)]
 pub struct LineCache {
-    pub n_invalid_before: u64,
-    pub lines: Vec<Option<Line>>,
-    pub n_invalid_after: u64,
+    /// Visual rows: with word wrap on, xi-core sends each wrapped part of a
+    /// line as a line of its own, only the first one carrying `line_num`.
+    height: u64,
+    chunks: Arc<BTreeMap<u64, Arc<Chunk>>>,
+    bytes: usize,
+    /// Maps logical line numbers to the row they start on, for the lines
+    /// whose first row is cached.
+    line_starts: Arc<BTreeMap<u64, u64>>,
+    budget: usize,
+    /// The lines the last update with text sent, first (inclusive) to last
+    /// (exclusive). xi-core sends what's around our `scroll` range, so
+    /// eviction keeps these.
+    focus: Option<(u64, u64)>,
+    /// From the last update, `None` when xi-core doesn't send annotations.
+    pub annotations: Option<Vec<Annotation>>,
+    /// Grows with every update. It follows xi-core's `rev` when updates carry
+    /// one, but also moves on for updates that keep `rev`, like cursor moves.
+    pub revision: u64,
+}
+
+impl Default for LineCache {
+    fn default() -> Self {
+        LineCache::new()
+    }
 }
 
 impl Data for LineCache {
+    /// Lines only change through `update`, which bumps the revision.
     fn same(&self, other: &Self) -> bool {
-        self.n_invalid_after == self.n_invalid_after
-            && self.n_invalid_before == self.n_invalid_before
-            && self.lines.len() == self.lines.len()
-            && self.lines.iter().zip(other.lines.iter()).all(|(_a, _b)| {
-                // a.same(b)
-                // todo: add lost line
-                return true;
-            })
+        self.revision == other.revision && self.height == other.height
     }
 }
 
 impl LineCache {
     pub fn new() -> Self {
         Self {
-            n_invalid_before: 0,
-            lines: Vec::new(),
-            n_invalid_after: 0,
+            height: 0,
+            chunks: Default::default(),
+            bytes: 0,
+            line_starts: Default::default(),
+            budget: DEFAULT_BUDGET,
+            focus: None,
+            annotations: None,
+            revision: 0,
+        }
+    }
+    /// A cache with `n_invalid_before` invalid lines, then `lines`, then
+    /// `n_invalid_after` invalid lines.
+    #[cfg(test)]
+    pub fn from_lines(
+        n_invalid_before: u64,
+        lines: Vec<Option<Line>>,
+        n_invalid_after: u64,
+    ) -> Self {
+        let mut chunks = Chunks::default();
+        chunks.push_invalid(n_invalid_before);
+        for line in lines {
+            match line {
+                Some(line) => chunks.push(line),
+                None => chunks.push_invalid(1),
+            }
+        }
+        chunks.push_invalid(n_invalid_after);
+        LineCache {
+            height: chunks.height,
+            chunks: Arc::new(chunks.chunks),
+            bytes: chunks.bytes,
+            line_starts: Arc::new(chunks.line_starts),
+            ..LineCache::new()
         }
     }
+    /// Sets how many bytes the lines may take, evicting right away when they
+    /// take more.
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        self.evict();
+    }
+    /// Roughly how many bytes the cached lines take.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
     pub fn height(&self) -> u64 {
-        self.n_invalid_before + self.lines.len() as u64 + self.n_invalid_after
+        self.height
+    }
+    /// The valid lines, in order.
+    pub fn lines(&self) -> impl Iterator<Item = &Line> {
+        self.chunks
+            .values()
+            .flat_map(|chunk| chunk.lines.iter().flatten())
+    }
+    /// Whether no line is invalid.
+    pub fn is_complete(&self) -> bool {
+        self.lines().count() as u64 == self.height
     }
     pub fn width(&self) -> usize {
-        self.lines
-            .iter()
-            .map(|l| match *l {
-                None => 0,
-                Some(ref l) => l.text.len(),
-            })
-            .max()
-            .unwrap_or(0)
+        self.lines().map(|l| l.text.len()).max().unwrap_or(0)
     }
     pub fn get_line(&self, n: u64) -> Option<&Line> {
-        if n < self.n_invalid_before || n > self.n_invalid_before + self.lines.len() as u64 {
+        if n >= self.height {
             return None;
         }
-        let ix = (n - self.n_invalid_before) as usize;
-        if let Some(&Some(ref line)) = self.lines.get(ix) {
-            Some(line)
-        } else {
-            None
-        }
+        let chunk = self.chunks.get(&(n / CHUNK_LINES))?;
+        chunk.lines[(n % CHUNK_LINES) as usize].as_ref()
+    }
+    /// The logical line number visual line `n` belongs to: xi-core only
+    /// sends `ln` with the first visual line of a wrapped line. `None` when
+    /// that first line isn't cached.
+    pub fn line_num_at(&self, n: u64) -> Option<u64> {
+        (0..=n)
+            .rev()
+            .map(|ix| self.get_line(ix))
+            .take_while(Option::is_some)
+            .flatten()
+            .find_map(|line| line.line_num)
+    }
+    /// The row logical line `line_num` starts on, when that row is cached.
+    pub fn row_of_line(&self, line_num: u64) -> Option<u64> {
+        self.line_starts.get(&line_num).copied()
     }
+    /// The row logical line `line_num` most likely starts on. Unless that row
+    /// is cached, it's counted from the closest cached line above, as if no
+    /// line in between wrapped.
+    pub fn estimate_row(&self, line_num: u64) -> u64 {
+        let row = match self.line_starts.range(..=line_num).next_back() {
+            Some((known, row)) => row + (line_num - known),
+            None => line_num.saturating_sub(1),
+        };
+        row.min(self.height.saturating_sub(1))
+    }
+    /// The runs of invalid lines between `first` (inclusive) and `last`
+    /// (exclusive), cut off at the end of the buffer.
     pub fn get_missing(&self, first: u64, last: u64) -> Vec<(u64, u64)> {
         let mut ret = Vec::new();
         let last = min(last, self.height());
-        assert!(first < last);
+        if first >= last {
+            return ret;
+        }
 
         let mut run = None;
         for ix in first..last {
-            if ix < self.n_invalid_before
-                || ix >= self.n_invalid_before + self.lines.len() as u64
-                || self.lines[(ix - self.n_invalid_before) as usize].is_none()
-            {
+            if self.get_line(ix).is_none() {
                 match run {
                     None => {
                         run = Some((ix, ix + 1));
@@ -114,98 +418,163 @@ impl LineCache {
         }
         ret
     }
-    /// Handle an xi-core update.
-    pub fn update(&mut self, update: Update) {
-        let mut new_invalid_before = 0;
-        let mut new_lines: Vec<Option<Line>> = Vec::new();
-        let mut new_invalid_after = 0;
+    /// The byte ranges of visual line `n` covered by annotations of
+    /// `annotation_type`. Ranges going on to the next line end past the
+    /// line's text.
+    pub fn annotation_ranges(
+        &self,
+        n: u64,
+        annotation_type: &AnnotationType,
+    ) -> Vec<(usize, usize)> {
+        let len = match self.get_line(n) {
+            Some(line) => line.text.len(),
+            None => return Vec::new(),
+        };
+        self.annotations
+            .iter()
+            .flatten()
+            .filter(|annotation| &annotation.annotation_type == annotation_type)
+            .flat_map(|annotation| annotation.ranges.iter())
+            .filter(|[start_line, _, end_line, _]| *start_line <= n && n <= *end_line)
+            .map(|&[start_line, start_col, end_line, end_col]| {
+                let start = if start_line == n {
+                    start_col as usize
+                } else {
+                    0
+                };
+                let end = if end_line == n {
+                    end_col as usize
+                } else {
+                    len + 1
+                };
+                (start, end)
+            })
+            .collect()
+    }
+    /// Handle an xi-core update, returning the logical lines it moved.
+    pub fn update(&mut self, update: Update) -> Vec<LineShift> {
+        self.revision = match update.rev {
+            Some(rev) if rev > self.revision => rev,
+            _ => self.revision + 1,
+        };
+        self.annotations = update.annotations;
 
+        let chunks = mem::take(&mut self.chunks);
+        let mut old = Chunks {
+            height: self.height,
+            chunks: Arc::try_unwrap(chunks).unwrap_or_else(|shared| (*shared).clone()),
+            bytes: self.bytes,
+            line_starts: BTreeMap::new(),
+        };
+        let mut new = Chunks::default();
+        let mut sent: Option<(u64, u64)> = None;
         let mut old_ix = 0_u64;
+        let mut shifts = Vec::new();
 
         for op in update.operations {
-            //debug!("lc before {}-- {} {:?} {}", op_type, new_invalid_before, new_lines, new_invalid_after);
             let n = op.nb_lines;
             match op.operation_type {
                 OperationType::Invalidate => {
                     trace!("invalidate n={}", n);
-                    if new_lines.is_empty() {
-                        new_invalid_before += n;
-                    } else {
-                        new_invalid_after += n;
-                    }
+                    new.push_invalid(n);
                 }
                 OperationType::Insert => {
-                    for _ in 0..new_invalid_after {
-                        new_lines.push(None)
-                    }
                     trace!("ins n={}", n);
-                    new_invalid_after = 0;
+                    let first = new.height;
                     for line in op.lines {
-                        new_lines.push(Some(line.into()));
+                        new.push(line.into());
                     }
+                    sent = Some(extend(sent, first, new.height));
                 }
                 OperationType::Copy_ => {
                     trace!("copy n={}", n);
-
-                    for _ in 0..new_invalid_after {
-                        new_lines.push(None)
+                    // evicted lines copy as invalid ones, `ln` renumbers
+                    // the lines after an edit above them
+                    shifts.extend(new.copy_from(&mut old, old_ix, n, op.line_num));
+                    old_ix += n;
+                }
+                OperationType::Skip => {
+                    trace!("skip n={}", n);
+                    old_ix += n;
+                }
+                OperationType::Update => {
+                    trace!("update n={}", n);
+                    // same text as the old lines, with new cursors and styles
+                    let first = new.height;
+                    let mut updates = op.lines.into_iter();
+                    for ix in old_ix..old_ix + n {
+                        match (old.take(ix), updates.next()) {
+                            (Some(old), Some(update)) => new.push(Line {
+                                text: old.text,
+                                cursor: update.cursor,
+                                styles: update.styles,
+                                line_num: update.line_num.or(old.line_num),
+                            }),
+                            _ => new.push_invalid(1),
+                        }
                     }
+                    sent = Some(extend(sent, first, new.height));
+                    old_ix += n;
+                }
+            }
+        }
 
-                    new_invalid_after = 0;
+        self.height = new.height;
+        self.chunks = Arc::new(new.chunks);
+        self.bytes = new.bytes;
+        self.line_starts = Arc::new(new.line_starts);
+        if sent.is_some() {
+            self.focus = sent;
+        }
+        self.evict();
+        shifts
+    }
 
-                    let mut n_remaining = n;
-                    if old_ix < self.n_invalid_before {
-                        let invalid = min(n, self.n_invalid_before - old_ix);
-                        if new_lines.is_empty() {
-                            new_invalid_before += invalid;
-                        } else {
-                            new_invalid_after += invalid;
-                        }
-                        old_ix += invalid;
-                        n_remaining -= invalid;
-                    }
-                    if n_remaining > 0 && old_ix < self.n_invalid_before + self.lines.len() as u64 {
-                        let n_copy = min(
-                            n_remaining,
-                            self.n_invalid_before + self.lines.len() as u64 - old_ix,
-                        );
-                        let start_ix = old_ix - self.n_invalid_before;
-
-                        for i in start_ix as usize..(start_ix + n_copy) as usize {
-                            if self.lines[i].is_none() {
-                                error!(
-                                    "line {}+{}={}, a copy source is none",
-                                    self.n_invalid_before,
-                                    i,
-                                    self.n_invalid_before + i as u64
-                                );
-                            }
-                        }
-                        new_lines.extend_from_slice(
-                            &self.lines[start_ix as usize..(start_ix + n_copy) as usize],
-                        );
+    /// Drops the chunks furthest from the focus until the lines fit in the
+    /// budget. The chunks holding the focus itself are always kept, and the
+    /// dropped lines are invalid until they're requested again.
+    fn evict(&mut self) {
+        if self.bytes <= self.budget {
+            return;
+        }
+        let (first, last) = self.focus.unwrap_or((0, 0));
+        let (first, last) = (first / CHUNK_LINES, last.saturating_sub(1) / CHUNK_LINES);
+        let distance = |chunk: u64| {
+            if chunk < first {
+                first - chunk
+            } else {
+                chunk.saturating_sub(last)
+            }
+        };
 
-                        old_ix += n_copy;
-                        n_remaining -= n_copy;
-                    }
-                    if new_lines.is_empty() {
-                        new_invalid_before += n_remaining;
-                    } else {
-                        new_invalid_after += n_remaining;
+        let mut far: Vec<u64> = self
+            .chunks
+            .keys()
+            .copied()
+            .filter(|chunk| distance(*chunk) > 0)
+            .collect();
+        far.sort_by_key(|chunk| distance(*chunk));
+        while self.bytes > self.budget {
+            let chunk = match far.pop() {
+                Some(chunk) => chunk,
+                None => break,
+            };
+            if let Some(evicted) = Arc::make_mut(&mut self.chunks).remove(&chunk) {
+                trace!(
+                    "evict lines {}..{}",
+                    chunk * CHUNK_LINES,
+                    (chunk + 1) * CHUNK_LINES
+                );
+                self.bytes -= evicted.bytes;
+                let evicted_starts = evicted.lines.iter().flatten().filter_map(|l| l.line_num);
+                let line_starts = Arc::make_mut(&mut self.line_starts);
+                for line_num in evicted_starts {
+                    if line_starts.get(&line_num).map(|row| row / CHUNK_LINES) == Some(chunk) {
+                        line_starts.remove(&line_num);
                     }
-                    old_ix += n_remaining;
                 }
-                OperationType::Skip => {
-                    trace!("skip n={}", n);
-                    old_ix += n;
-                }
-                _ => {}
             }
         }
-        self.n_invalid_before = new_invalid_before;
-        self.lines = new_lines;
-        self.n_invalid_after = new_invalid_after;
-        //debug!("lc after update {:?}", self);
     }
 
     /// Returns true if this Linecache only contains one line, which doesn't contain any text
@@ -221,3 +590,346 @@ impl LineCache {
         false
     }
 }
+
+/// Grows the range `sent` to cover `first..last` as well.
+fn extend(sent: Option<(u64, u64)>, first: u64, last: u64) -> (u64, u64) {
+    match sent {
+        Some((start, end)) => (start.min(first), end.max(last)),
+        None => (first, last),
+    }
+}
+
+#[cfg(test)]
+mod fuzz;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn line(text: &str) -> Option<Line> {
+        Some(Line {
+            text: text.to_string(),
+            cursor: vec![],
+            styles: vec![],
+            line_num: None,
+        })
+    }
+
+    #[test]
+    fn get_missing_finds_invalid_runs() {
+        let cache = LineCache::from_lines(2, vec![line("a"), None, None, line("b")], 3);
+
+        assert_eq!(vec![(0, 2), (3, 5), (6, 9)], cache.get_missing(0, 100));
+        assert_eq!(vec![(4, 5)], cache.get_missing(4, 6));
+        assert!(cache.get_missing(5, 6).is_empty());
+        assert!(cache.get_missing(9, 20).is_empty());
+    }
+
+    #[test]
+    fn wrapped_lines_take_the_number_of_their_first_line() {
+        let numbered = |line_num| {
+            let mut numbered = line("wrapped ");
+            numbered.as_mut().unwrap().line_num = Some(line_num);
+            numbered
+        };
+        let lines = vec![
+            numbered(2),
+            line("a"),
+            line("b"),
+            numbered(3),
+            None,
+            line("c"),
+        ];
+        let cache = LineCache::from_lines(1, lines, 0);
+
+        assert_eq!(None, cache.line_num_at(0));
+        assert_eq!(Some(2), cache.line_num_at(1));
+        assert_eq!(Some(2), cache.line_num_at(3));
+        assert_eq!(Some(3), cache.line_num_at(4));
+        // the start of this one isn't cached
+        assert_eq!(None, cache.line_num_at(6));
+    }
+
+    #[test]
+    fn logical_lines_map_to_their_first_row() {
+        let mut cache = LineCache::new();
+        cache.update(update(json!({
+            "ops": [
+                { "op": "invalidate", "n": 2 },
+                { "op": "ins", "n": 4, "lines": [
+                    { "text": "wrapped ", "styles": [], "ln": 3 },
+                    { "text": "twice ", "styles": [] },
+                    { "text": "over\n", "styles": [] },
+                    { "text": "short\n", "styles": [], "ln": 4 }
+                ] },
+                { "op": "invalidate", "n": 10 }
+            ],
+            "pristine": true
+        })));
+
+        assert_eq!(Some(2), cache.row_of_line(3));
+        assert_eq!(Some(5), cache.row_of_line(4));
+        assert_eq!(None, cache.row_of_line(1));
+        assert_eq!(1, cache.estimate_row(2));
+        assert_eq!(5, cache.estimate_row(4));
+        // counted on from line 4
+        assert_eq!(8, cache.estimate_row(7));
+        assert_eq!(15, cache.estimate_row(100));
+
+        // a line inserted above moves the others down a row and a number
+        cache.update(update(json!({
+            "ops": [
+                { "op": "ins", "n": 1, "lines": [{ "text": "new\n", "styles": [], "ln": 1 }] },
+                { "op": "copy", "n": 16, "ln": 2 }
+            ],
+            "pristine": false
+        })));
+        assert_eq!(Some(0), cache.row_of_line(1));
+        assert_eq!(Some(3), cache.row_of_line(4));
+        assert_eq!(Some(6), cache.row_of_line(5));
+        assert_eq!(1, cache.estimate_row(2));
+        assert_eq!(9, cache.estimate_row(8));
+    }
+
+    fn update(update: serde_json::Value) -> Update {
+        serde_json::from_value(json!({ "view_id": "view-id-1", "update": update })).unwrap()
+    }
+
+    #[test]
+    fn update_op_refreshes_cursor_and_styles() {
+        let mut cache = LineCache::new();
+        cache.update(update(json!({
+            "ops": [{ "op": "ins", "n": 2, "lines": [
+                { "text": "one\n", "cursor": [0], "styles": [], "ln": 1 },
+                { "text": "two\n", "styles": [], "ln": 2 }
+            ] }],
+            "pristine": true
+        })));
+
+        cache.update(update(json!({
+            "ops": [{ "op": "update", "n": 2, "lines": [
+                { "styles": [] },
+                { "cursor": [2], "styles": [0, 2, 0] }
+            ] }],
+            "pristine": true
+        })));
+
+        let one = cache.get_line(0).unwrap();
+        assert_eq!("one\n", one.text);
+        assert!(one.cursor.is_empty());
+        assert_eq!(Some(1), one.line_num);
+        let two = cache.get_line(1).unwrap();
+        assert_eq!("two\n", two.text);
+        assert_eq!(vec![2], two.cursor);
+        assert_eq!(1, two.styles.len());
+        assert_eq!(Some(2), two.line_num);
+    }
+
+    #[test]
+    fn update_op_over_invalid_lines_stays_invalid() {
+        let mut cache = LineCache::new();
+        cache.update(update(json!({
+            "ops": [
+                { "op": "invalidate", "n": 1 },
+                { "op": "ins", "n": 1, "lines": [{ "text": "b", "styles": [] }] }
+            ],
+            "pristine": true
+        })));
+        cache.update(update(json!({
+            "ops": [{ "op": "update", "n": 2, "lines": [{ "styles": [] }, { "cursor": [1], "styles": [] }] }],
+            "pristine": true
+        })));
+
+        assert!(cache.get_line(0).is_none());
+        assert_eq!(vec![1], cache.get_line(1).unwrap().cursor);
+    }
+
+    #[test]
+    fn annotations_split_into_line_ranges() {
+        let mut cache = LineCache::new();
+        cache.update(update(json!({
+            "ops": [{ "op": "ins", "n": 3, "lines": [
+                { "text": "abc\n", "styles": [] },
+                { "text": "de\n", "styles": [] },
+                { "text": "fgh", "styles": [] }
+            ] }],
+            "annotations": [
+                { "type": "selection", "ranges": [[0, 1, 2, 2]], "payloads": null, "n": 1 },
+                { "type": "find", "ranges": [[1, 0, 1, 1], [2, 0, 2, 1]], "payloads": null, "n": 2 }
+            ],
+            "pristine": true
+        })));
+
+        let selection = AnnotationType::Selection;
+        assert_eq!(vec![(1, 5)], cache.annotation_ranges(0, &selection));
+        assert_eq!(vec![(0, 4)], cache.annotation_ranges(1, &selection));
+        assert_eq!(vec![(0, 2)], cache.annotation_ranges(2, &selection));
+        assert_eq!(
+            vec![(0, 1)],
+            cache.annotation_ranges(1, &AnnotationType::Find)
+        );
+        assert!(cache.annotation_ranges(0, &AnnotationType::Find).is_empty());
+
+        cache.update(update(
+            json!({ "ops": [{ "op": "copy", "n": 3 }], "pristine": true }),
+        ));
+        assert_eq!(None, cache.annotations);
+    }
+
+    #[test]
+    fn copies_are_renumbered_from_their_ln() {
+        let mut cache = LineCache::new();
+        cache.update(update(json!({
+            "ops": [{ "op": "ins", "n": 4, "lines": [
+                { "text": "one\n", "styles": [], "ln": 1 },
+                { "text": "two ", "styles": [], "ln": 2 },
+                { "text": "wrapped\n", "styles": [] },
+                { "text": "three\n", "styles": [], "ln": 3 }
+            ] }],
+            "pristine": true
+        })));
+
+        // a line inserted above the others moves them down by one
+        let shifts = cache.update(update(json!({
+            "ops": [
+                { "op": "ins", "n": 1, "lines": [{ "text": "new\n", "styles": [], "ln": 1 }] },
+                { "op": "copy", "n": 4, "ln": 2 }
+            ],
+            "pristine": false
+        })));
+        let numbers: Vec<_> = (0..5).map(|n| cache.line_num_at(n)).collect();
+        assert_eq!(vec![Some(1), Some(2), Some(3), Some(3), Some(4)], numbers);
+        let shift = LineShift {
+            first: 1,
+            last: None,
+            delta: 1,
+        };
+        assert_eq!(vec![shift], shifts);
+        assert_eq!(Some(4), shift.apply(3));
+        assert!(!shift.covers(1));
+
+        // a copy may start on the second row of a wrapped line
+        cache.update(update(json!({
+            "ops": [
+                { "op": "skip", "n": 3 },
+                { "op": "copy", "n": 2, "ln": 1 }
+            ],
+            "pristine": false
+        })));
+        assert_eq!(None, cache.get_line(0).unwrap().line_num);
+        assert_eq!(Some(2), cache.get_line(1).unwrap().line_num);
+    }
+
+    #[test]
+    fn updates_change_the_revision() {
+        let mut cache = LineCache::new();
+        let before = cache.clone();
+        assert!(cache.same(&before));
+
+        cache.update(update(json!({
+            "ops": [{ "op": "ins", "n": 1, "lines": [{ "text": "a", "styles": [] }] }],
+            "pristine": true,
+            "rev": 7
+        })));
+        assert_eq!(7, cache.revision);
+        assert!(!cache.same(&before));
+
+        // a cursor move keeps xi-core's rev
+        let typed = cache.clone();
+        cache.update(update(json!({
+            "ops": [{ "op": "update", "n": 1, "lines": [{ "cursor": [1], "styles": [] }] }],
+            "pristine": true,
+            "rev": 7
+        })));
+        assert_eq!(8, cache.revision);
+        assert!(!cache.same(&typed));
+        assert!(cache.same(&cache.clone()));
+    }
+
+    fn lines(first: u64, n: u64) -> serde_json::Value {
+        let lines: Vec<_> = (first..first + n)
+            .map(|ix| json!({ "text": format!("line {}\n", ix), "styles": [] }))
+            .collect();
+        json!({ "op": "ins", "n": n, "lines": lines })
+    }
+
+    #[test]
+    fn clones_share_the_chunks_an_update_leaves_alone() {
+        let mut cache = LineCache::new();
+        cache.update(update(json!({ "ops": [lines(0, 768)], "pristine": true })));
+        let before = cache.clone();
+
+        // editing line 300 only touches the second chunk
+        cache.update(update(json!({
+            "ops": [
+                { "op": "copy", "n": 300 },
+                { "op": "skip", "n": 1 },
+                { "op": "ins", "n": 1, "lines": [{ "text": "edited\n", "styles": [] }] },
+                { "op": "copy", "n": 467 }
+            ],
+            "pristine": false
+        })));
+        let shared = |key| Arc::ptr_eq(&before.chunks[&key], &cache.chunks[&key]);
+        assert!(shared(0));
+        assert!(!shared(1));
+        assert!(shared(2));
+        assert_eq!("edited\n", cache.get_line(300).unwrap().text);
+        assert_eq!("line 301\n", cache.get_line(301).unwrap().text);
+        // the clone keeps its lines
+        assert_eq!("line 300\n", before.get_line(300).unwrap().text);
+        assert_eq!("line 301\n", before.get_line(301).unwrap().text);
+    }
+
+    #[test]
+    fn copies_move_lines_across_chunks() {
+        let mut cache = LineCache::new();
+        cache.update(update(json!({
+            "ops": [{ "op": "invalidate", "n": 1000 }, lines(1000, 600), { "op": "invalidate", "n": 100000 }],
+            "pristine": true
+        })));
+        assert_eq!(101600, cache.height());
+
+        // a line inserted at 1200 shifts the rest down
+        cache.update(update(json!({
+            "ops": [
+                { "op": "copy", "n": 1200 },
+                { "op": "ins", "n": 1, "lines": [{ "text": "new\n", "styles": [] }] },
+                { "op": "copy", "n": 100400 }
+            ],
+            "pristine": true
+        })));
+        assert_eq!(101601, cache.height());
+        assert_eq!("line 1199\n", cache.get_line(1199).unwrap().text);
+        assert_eq!("new\n", cache.get_line(1200).unwrap().text);
+        assert_eq!("line 1599\n", cache.get_line(1600).unwrap().text);
+        assert_eq!(
+            vec![(0, 1000), (1601, 101601)],
+            cache.get_missing(0, 200000)
+        );
+    }
+
+    #[test]
+    fn eviction_drops_lines_far_from_the_focus() {
+        let mut cache = LineCache::new();
+        cache.update(update(json!({
+            "ops": [lines(0, 2000), { "op": "invalidate", "n": 8000 }],
+            "pristine": true
+        })));
+        let all = cache.bytes();
+        // what xi-core sent last is kept, however small the budget
+        cache.set_budget(all / 2);
+        assert_eq!(all, cache.bytes());
+
+        // scrolling to the end brings its lines in, and the start goes
+        cache.update(update(json!({
+            "ops": [{ "op": "copy", "n": 2000 }, { "op": "invalidate", "n": 7500 }, lines(9500, 500)],
+            "pristine": true
+        })));
+        assert!(cache.bytes() <= all / 2);
+        assert!(cache.get_line(9999).is_some());
+        assert!(cache.get_line(1999).is_some());
+        assert!(cache.get_line(0).is_none());
+        assert_eq!(vec![(0, 10)], cache.get_missing(0, 10));
+    }
+}
@@ -0,0 +1,512 @@
+//! Checks `LineCache::update` against a plain `Vec` of lines, for random
+//! edits to a wrapped document turned into the update ops xi-core would send
+//! for them. The expected line numbers come from the document itself.
+//!
+//! A failing case panics with its updates and the document's shape after
+//! each as a fixture. Saved in
+//! `src/linecache/fixtures/`, it is replayed by `fixtures_replay` from then on.
+//! `LINECACHE_FUZZ_SEED` and `LINECACHE_FUZZ_CASES` pick which cases run.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use super::LineCache;
+use crate::Update;
+
+const CASES: u64 = 100;
+const UPDATES_PER_CASE: usize = 20;
+
+/// xorshift64*, so a seed always replays the same case.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// A number in `0..n`, or 0 when `n` is 0.
+    fn below(&mut self, n: u64) -> u64 {
+        if n == 0 {
+            0
+        } else {
+            self.next() % n
+        }
+    }
+
+    fn chance(&mut self, percent: u64) -> bool {
+        self.below(100) < percent
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct ModelLine {
+    text: String,
+    cursor: Vec<u64>,
+    line_num: Option<u64>,
+}
+
+/// The reference: one slot per line, rebuilt from scratch for every update.
+#[derive(Clone, Default)]
+struct Model {
+    lines: Vec<Option<ModelLine>>,
+}
+
+impl Model {
+    /// Applies the ops of `update`. With the `shape` of the document, the
+    /// rows per logical line, the valid rows are numbered from it; without,
+    /// they keep the numbers they were sent with.
+    fn apply(&mut self, update: &Value, shape: Option<&[u64]>) -> Result<(), String> {
+        let mut lines = Vec::new();
+        let mut old_ix = 0;
+        for op in update["ops"].as_array().unwrap() {
+            let n = op["n"].as_u64().unwrap() as usize;
+            let sent = op["lines"].as_array().cloned().unwrap_or_default();
+            match op["op"].as_str().unwrap() {
+                "copy" => {
+                    if shape.is_none() && !op["ln"].is_null() {
+                        return Err("a copy with `ln` needs the document's shape".to_string());
+                    }
+                    let old = self.lines.get(old_ix..old_ix + n).ok_or_else(|| {
+                        format!("copy past the end of {} lines", self.lines.len())
+                    })?;
+                    lines.extend(old.iter().cloned());
+                    old_ix += n;
+                }
+                "skip" => old_ix += n,
+                "invalidate" => lines.extend((0..n).map(|_| None)),
+                "ins" => lines.extend(sent.iter().map(|line| {
+                    Some(ModelLine {
+                        text: line["text"].as_str().unwrap_or_default().to_string(),
+                        cursor: cursor(line),
+                        line_num: line["ln"].as_u64(),
+                    })
+                })),
+                "update" => {
+                    for i in 0..n {
+                        let old = self.lines.get(old_ix + i).cloned().flatten();
+                        lines.push(old.and_then(|old| {
+                            let line = sent.get(i)?;
+                            Some(ModelLine {
+                                text: old.text,
+                                cursor: cursor(line),
+                                line_num: line["ln"].as_u64().or(old.line_num),
+                            })
+                        }));
+                    }
+                    old_ix += n;
+                }
+                op => return Err(format!("unknown op {}", op)),
+            }
+        }
+        if let Some(shape) = shape {
+            let numbers = line_nums(shape);
+            if numbers.len() != lines.len() {
+                return Err(format!(
+                    "{} rows, but the document has {}",
+                    lines.len(),
+                    numbers.len()
+                ));
+            }
+            for (line, line_num) in lines.iter_mut().zip(numbers) {
+                if let Some(line) = line {
+                    line.line_num = line_num;
+                }
+            }
+        }
+        self.lines = lines;
+        Ok(())
+    }
+}
+
+/// The `line_num` of each row of a document with `shape`: the logical line
+/// on its first row, nothing on the rows it wraps onto.
+fn line_nums(shape: &[u64]) -> Vec<Option<u64>> {
+    shape
+        .iter()
+        .zip(1..)
+        .flat_map(|(rows, line_num)| (0..*rows).map(move |row| Some(line_num).filter(|_| row == 0)))
+        .collect()
+}
+
+fn cursor(line: &Value) -> Vec<u64> {
+    line["cursor"]
+        .as_array()
+        .map(|cursor| cursor.iter().filter_map(Value::as_u64).collect())
+        .unwrap_or_default()
+}
+
+fn to_update(update: &Value) -> Update {
+    serde_json::from_value(json!({ "view_id": "view-id-1", "update": update })).unwrap()
+}
+
+/// Compares the cache with the model. With `exact` unset some lines may have
+/// been evicted, so only the valid ones are compared, and not by number:
+/// an evicted row is renumbered as if it started a line, wrapped or not.
+fn check(cache: &LineCache, model: &Model, exact: bool) -> Result<(), String> {
+    if cache.height() != model.lines.len() as u64 {
+        return Err(format!(
+            "height is {}, expected {}",
+            cache.height(),
+            model.lines.len()
+        ));
+    }
+    for (ix, expected) in model.lines.iter().enumerate() {
+        let actual = cache.get_line(ix as u64).map(|line| ModelLine {
+            text: line.text.clone(),
+            cursor: line.cursor.clone(),
+            line_num: line.line_num,
+        });
+        let same = match (&actual, expected) {
+            (Some(actual), Some(expected)) if !exact => {
+                actual.text == expected.text && actual.cursor == expected.cursor
+            }
+            _ => actual == *expected,
+        };
+        if !same && (exact || actual.is_some()) {
+            return Err(format!(
+                "line {} is {:?}, expected {:?}",
+                ix, actual, expected
+            ));
+        }
+    }
+    if exact {
+        let mut missing: Vec<(u64, u64)> = Vec::new();
+        for (ix, line) in model.lines.iter().enumerate() {
+            let ix = ix as u64;
+            if line.is_none() {
+                match missing.last_mut() {
+                    Some((_, last)) if *last == ix => *last = ix + 1,
+                    _ => missing.push((ix, ix + 1)),
+                }
+            }
+        }
+        let actual = cache.get_missing(0, model.lines.len() as u64);
+        if actual != missing {
+            return Err(format!("missing {:?}, expected {:?}", actual, missing));
+        }
+    }
+    Ok(())
+}
+
+/// One row of a document: its text and the logical line it belongs to.
+struct Row {
+    text: String,
+    line_num: u64,
+    first: bool,
+}
+
+/// Makes up edits to a wrapped document and the ops that carry them over
+/// to a view which only knows some of its rows, the way xi-core does: rows
+/// the view has are copied or updated, the others invalidated or sent, and
+/// `ln` goes with copies and the first row of each logical line.
+struct Editor {
+    rng: Rng,
+    /// The logical lines, each wrapped into one or more rows.
+    doc: Vec<Vec<String>>,
+    /// The rows of `doc`.
+    rows: Vec<Row>,
+    /// Which rows the view has, as far as xi-core knows.
+    valid: Vec<bool>,
+    made: u64,
+    ops: Vec<Value>,
+    /// Rows the current update sends with `ins`, which eviction must keep.
+    sent: Vec<u64>,
+    old_ix: u64,
+    new_ix: u64,
+    new_valid: Vec<bool>,
+}
+
+impl Editor {
+    fn new(seed: u64) -> Editor {
+        Editor {
+            rng: Rng::new(seed),
+            doc: Vec::new(),
+            rows: Vec::new(),
+            valid: Vec::new(),
+            made: 0,
+            ops: Vec::new(),
+            sent: Vec::new(),
+            old_ix: 0,
+            new_ix: 0,
+            new_valid: Vec::new(),
+        }
+    }
+
+    /// The rows per logical line.
+    fn shape(&self) -> Vec<u64> {
+        self.doc.iter().map(|rows| rows.len() as u64).collect()
+    }
+
+    /// A new logical line, wrapped over a few rows now and then.
+    fn new_line(&mut self) -> Vec<String> {
+        self.made += 1;
+        // multi-byte text makes the lines differ in size
+        let filler = ["", "é", "漢字", "🦀🦀"][self.rng.below(4) as usize];
+        let wraps = if self.rng.chance(70) {
+            0
+        } else {
+            self.rng.below(3) + 1
+        };
+        let mut rows = vec![format!("line {} ", self.made)];
+        for wrap in 0..wraps {
+            rows.push(format!("wrap {} ", wrap + 1));
+        }
+        let last = rows.last_mut().unwrap();
+        last.push_str(&filler.repeat(self.rng.below(8) as usize));
+        last.push('\n');
+        rows
+    }
+
+    fn push(&mut self, op: &str, n: u64) {
+        self.ops.push(json!({ "op": op, "n": n }));
+    }
+
+    fn push_lines(&mut self, op: &str, n: u64) {
+        let lines: Vec<Value> = (self.new_ix..self.new_ix + n)
+            .map(|ix| {
+                let row = &self.rows[ix as usize];
+                let mut line = json!({ "styles": [] });
+                if op == "ins" {
+                    line["text"] = json!(row.text);
+                    self.sent.push(ix);
+                }
+                if row.first {
+                    line["ln"] = json!(row.line_num);
+                }
+                if self.rng.chance(30) {
+                    line["cursor"] = json!([self.rng.below(4)]);
+                }
+                line
+            })
+            .collect();
+        self.ops.push(json!({ "op": op, "n": n, "lines": lines }));
+        self.new_ix += n;
+        self.new_valid.extend((0..n).map(|_| true));
+    }
+
+    fn invalidate(&mut self, n: u64) {
+        self.push("invalidate", n);
+        self.new_ix += n;
+        self.new_valid.extend((0..n).map(|_| false));
+    }
+
+    /// Carries `n` old rows over, in runs that are copied, updated,
+    /// invalidated or sent again. Only rows the view has are copied or
+    /// updated.
+    fn carry(&mut self, mut n: u64) {
+        while n > 0 {
+            let mut run = if self.rng.chance(50) {
+                n
+            } else {
+                self.rng.below(n) + 1
+            };
+            let old = self.old_ix as usize;
+            let valid = self.valid[old];
+            run = self.valid[old..old + run as usize]
+                .iter()
+                .take_while(|v| **v == valid)
+                .count() as u64;
+            match self.rng.below(100) {
+                0..=39 if valid => {
+                    let ln = self.rows[self.new_ix as usize].line_num;
+                    self.ops.push(json!({ "op": "copy", "n": run, "ln": ln }));
+                    self.new_ix += run;
+                    self.new_valid.extend((0..run).map(|_| true));
+                }
+                40..=54 if valid => self.push_lines("update", run),
+                0..=54 | 55..=74 => {
+                    self.push("skip", run);
+                    self.invalidate(run);
+                }
+                _ => {
+                    self.push("skip", run);
+                    self.push_lines("ins", run);
+                }
+            }
+            self.old_ix += run;
+            n -= run;
+        }
+    }
+
+    /// Replaces a few logical lines of the document, or none to resend some
+    /// rows the way a scroll does, and returns the update for it.
+    fn edit(&mut self) -> Value {
+        let len = self.doc.len() as u64;
+        let start = self.rng.below(len + 1);
+        let removed = self.rng.below((len - start).min(5) + 1);
+        let added = if self.doc.is_empty() {
+            self.rng.below(300) + 1
+        } else {
+            self.rng.below(4)
+        };
+        let rows_of = |lines: &[Vec<String>]| lines.iter().map(|rows| rows.len() as u64).sum();
+        let old_rows: u64 = rows_of(&self.doc);
+        let start_row: u64 = rows_of(&self.doc[..start as usize]);
+        let end = (start + removed) as usize;
+        let removed_rows: u64 = rows_of(&self.doc[start as usize..end]);
+        let lines: Vec<Vec<String>> = (0..added).map(|_| self.new_line()).collect();
+        let added_rows: u64 = rows_of(&lines);
+        self.doc.splice(start as usize..end, lines);
+        self.rows = self
+            .doc
+            .iter()
+            .zip(1..)
+            .flat_map(|(rows, line_num)| {
+                rows.iter().enumerate().map(move |(ix, text)| Row {
+                    text: text.clone(),
+                    line_num,
+                    first: ix == 0,
+                })
+            })
+            .collect();
+
+        self.ops.clear();
+        self.sent.clear();
+        self.old_ix = 0;
+        self.new_ix = 0;
+        self.new_valid.clear();
+        self.carry(start_row);
+        if removed_rows > 0 {
+            self.push("skip", removed_rows);
+            self.old_ix += removed_rows;
+        }
+        if added_rows > 0 {
+            if self.rng.chance(70) {
+                self.push_lines("ins", added_rows);
+            } else {
+                self.invalidate(added_rows);
+            }
+        }
+        self.carry(old_rows - start_row - removed_rows);
+        self.valid = std::mem::take(&mut self.new_valid);
+        json!({ "ops": self.ops, "pristine": false })
+    }
+}
+
+/// Replays `updates`, checking the cache after each one.
+fn replay(
+    updates: &[Value],
+    shapes: Option<&[Value]>,
+    budget: Option<usize>,
+) -> Result<(), String> {
+    let mut cache = LineCache::new();
+    if let Some(budget) = budget {
+        cache.set_budget(budget);
+    }
+    let mut model = Model::default();
+    for (i, update) in updates.iter().enumerate() {
+        let shape: Option<Vec<u64>> = shapes.map(|shapes| {
+            shapes[i]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|rows| rows.as_u64().unwrap())
+                .collect()
+        });
+        model
+            .apply(update, shape.as_deref())
+            .map_err(|e| format!("update {}: {}", i, e))?;
+        cache.update(to_update(update));
+        check(&cache, &model, budget.is_none()).map_err(|e| format!("update {}: {}", i, e))?;
+    }
+    Ok(())
+}
+
+/// A failing case: its updates, the document's shape after each, the
+/// budget and what went wrong.
+type Failure = (Vec<Value>, Vec<Vec<u64>>, Option<usize>, String);
+
+fn run_case(seed: u64) -> Result<(), Failure> {
+    let mut editor = Editor::new(seed);
+    let budget = if editor.rng.chance(30) {
+        Some(editor.rng.below(64 * 1024) as usize)
+    } else {
+        None
+    };
+    let mut cache = LineCache::new();
+    if let Some(budget) = budget {
+        cache.set_budget(budget);
+    }
+    let mut model = Model::default();
+    let mut updates = Vec::new();
+    let mut shapes = Vec::new();
+    for _ in 0..UPDATES_PER_CASE {
+        let update = editor.edit();
+        updates.push(update.clone());
+        shapes.push(editor.shape());
+        let fail = |e: String| (updates.clone(), shapes.clone(), budget, e);
+
+        let old_model = model.clone();
+        model.apply(&update, Some(&editor.shape())).map_err(fail)?;
+        for (ix, line) in model.lines.iter().enumerate() {
+            if let Some(line) = line {
+                // a broken generator would make the model useless
+                assert_eq!(editor.rows[ix].text, line.text, "seed {}", seed);
+            }
+        }
+        // druid keeps clones of the app state around, they must not change
+        let old_cache = cache.clone();
+        cache.update(to_update(&update));
+        check(&cache, &model, budget.is_none()).map_err(fail)?;
+        check(&old_cache, &old_model, budget.is_none())
+            .map_err(|e| fail(format!("the clone changed: {}", e)))?;
+        for ix in &editor.sent {
+            if cache.get_line(*ix).is_none() {
+                return Err(fail(format!("line {} was just sent, but evicted", ix)));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn env_u64(name: &str) -> Option<u64> {
+    env::var(name).ok().and_then(|value| value.parse().ok())
+}
+
+#[test]
+fn updates_match_the_model() {
+    let first = env_u64("LINECACHE_FUZZ_SEED").unwrap_or(0);
+    let cases = env_u64("LINECACHE_FUZZ_CASES").unwrap_or(CASES);
+    for seed in first..first + cases {
+        if let Err((updates, shapes, budget, error)) = run_case(seed) {
+            let fixture = json!({ "budget": budget, "updates": updates, "shapes": shapes });
+            panic!(
+                "seed {}: {}\nsave this in src/linecache/fixtures/ to keep it as a regression test:\n{}",
+                seed,
+                error,
+                serde_json::to_string(&fixture).unwrap()
+            );
+        }
+    }
+}
+
+#[test]
+fn fixtures_replay() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/linecache/fixtures");
+    let mut paths: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "json"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+    for path in paths {
+        let fixture: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let budget = fixture["budget"].as_u64().map(|budget| budget as usize);
+        let updates = fixture["updates"].as_array().unwrap();
+        let shapes = fixture["shapes"].as_array().map(Vec::as_slice);
+        if let Err(error) = replay(updates, shapes, budget) {
+            panic!("{}: {}", path.display(), error);
+        }
+    }
+}
@@ -12,19 +12,20 @@ use std::sync::{Arc, Mutex};
 use std::thread;
 
 use druid::widget::prelude::*;
-use druid::widget::{Flex, Label, WidgetExt};
+use druid::widget::{Either, Flex, Label, SizedBox, WidgetExt};
 use druid::{AppLauncher, Color, Target, UnitPoint, WindowDesc};
 use log::*;
 
 use app_state::AppState;
 use print::menu;
-use print::text_edit_view::TextEditView;
 use rpc::client::Client;
+use rpc::recorder::Recorder;
 pub use rpc::structs::{
-    Alert, AvailableLanguages, AvailablePlugins, AvailableThemes, ConfigChanged, ConfigChanges,
-    FindStatus, LanguageChanged, Line, MeasureWidth, ModifySelection, Operation, OperationType,
-    PluginStarted, PluginStopped, Position, Query, ReplaceStatus, ScrollTo, Status, Style,
-    StyleDef, ThemeChanged, ThemeSettings, Update, UpdateCmds, ViewId,
+    Alert, Annotation, AnnotationType, AvailableLanguages, AvailablePlugins, AvailableThemes,
+    ConfigChanged, ConfigChanges, EditCommand, FindStatus, GestureType, LanguageChanged, Line,
+    MeasureWidth, ModifySelection, Operation, OperationType, PluginStarted, PluginStopped,
+    Position, Query, ReplaceStatus, ScrollTo, Status, Style, StyleDef, ThemeChanged, ThemeSettings,
+    Update, UpdateCmds, ViewId,
 };
 pub use support::line;
 
@@ -33,7 +34,10 @@ use crate::app_delegate::Delegate;
 use crate::app_state::Workspace;
 use crate::components::icon_button::IconButton;
 use crate::print::edit_view::EditView;
-use crate::print::ProjectToolWindow;
+use crate::print::find_bar::find_bar;
+use crate::print::goto_bar::goto_bar;
+use crate::print::toasts::ToastHost;
+use crate::print::{EventLogToolWindow, PluginToolWindow, ProjectToolWindow};
 use crate::support::directory;
 
 use self::print::bar_support::text_count;
@@ -68,13 +72,25 @@ fn status_bar() -> impl Widget<AppState> {
     })
     .with_text_color(Color::BLACK);
 
-    Flex::row()
+    let language = Label::new(|data: &AppState, _env: &Env| {
+        let view = data.views.focused();
+        view.and_then(|view| view.language.clone())
+            .unwrap_or_default()
+    })
+    .with_text_color(Color::BLACK);
+
+    let words = Flex::row()
         .with_default_spacer()
         .with_flex_child(Label::new("words: ").with_text_color(Color::BLACK), 1.0)
         .with_default_spacer()
         .with_flex_child(label, 1.0)
         .with_default_spacer()
-        .lens(AppState::workspace)
+        .lens(AppState::workspace);
+
+    Flex::row()
+        .with_flex_child(words, 1.0)
+        .with_child(language)
+        .with_default_spacer()
         .padding(5.0)
         .align_horizontal(UnitPoint::LEFT)
 }
@@ -94,9 +110,18 @@ fn center() -> impl Widget<AppState> {
     Flex::row()
         .with_child(ProjectToolWindow::new())
         .with_default_spacer()
-        .with_flex_child(TextEditView::new().center(), 1.0)
-        .with_default_spacer()
-        .with_flex_child(EditView::new().center(), 1.0)
+        .with_flex_child(
+            Flex::column()
+                .with_child(find_bar())
+                .with_child(goto_bar())
+                .with_flex_child(EditView::new().center(), 1.0),
+            1.0,
+        )
+        .with_child(Either::new(
+            |data: &AppState, _env| data.params.show_plugins,
+            PluginToolWindow::new(),
+            SizedBox::empty(),
+        ))
         .padding(1.0)
         .expand_height()
         .expand_width()
@@ -104,12 +129,18 @@ fn center() -> impl Widget<AppState> {
 }
 
 fn make_ui() -> impl Widget<AppState> {
-    Flex::column()
+    let ui = Flex::column()
         .with_child(navigation_bar())
         .with_flex_child(center(), 1.0)
+        .with_child(Either::new(
+            |data: &AppState, _env| data.params.show_event_log,
+            EventLogToolWindow::new(),
+            SizedBox::empty(),
+        ))
         .with_child(bottom_tool_window())
         .with_child(status_bar())
-        .background(crate::theme::BACKGROUND_COLOR)
+        .background(crate::theme::BACKGROUND_COLOR);
+    ToastHost::new(ui)
 }
 
 #[cfg(windows)]
@@ -128,7 +159,22 @@ pub fn main() {
         .menu(menu::make_menu)
         .title(title);
 
-    let (client, rpc_receiver) = Client::new();
+    let mut init = directory::read_config();
+    let (client, rpc_receiver) = match Client::with_transport(init.transport.transport()) {
+        Ok(connection) => connection,
+        Err(err) => {
+            error!("failed to start xi-core with {:?}: {}", init.transport, err);
+            Client::new()
+        }
+    };
+
+    if init.record_rpc {
+        match directory::recordings_dir().map(|dir| Recorder::create(&dir)) {
+            Some(Ok(recorder)) => client.start_recording(recorder),
+            Some(Err(err)) => error!("failed to start rpc recording: {}", err),
+            None => error!("failed to start rpc recording: lost home dir"),
+        }
+    }
 
     let launcher = AppLauncher::with_window(main_window);
     let handler = launcher.get_external_handle();
@@ -136,30 +182,29 @@ pub fn main() {
     thread::spawn(move || loop {
         match rpc_receiver.recv() {
             Ok(operations) => {
-                handler
-                    .submit_command(print_command::XI_EVENT, Box::new(operations), Target::Auto)
-                    .expect("Failed to send command");
+                let result = handler.submit_command(
+                    print_command::XI_EVENT,
+                    Box::new(operations),
+                    Target::Auto,
+                );
+                if let Err(err) = result {
+                    error!("failed to submit xi event: {:?}", err);
+                    break;
+                }
             }
             Err(err) => {
-                error!("error: {:?}", err);
-                panic!("{:?}", err);
+                error!("xi-core channel closed: {:?}", err);
+                break;
             }
         }
     });
 
-    let mut init = directory::read_config();
     let client = Arc::new(Mutex::new(client));
     client
         .lock()
         .unwrap()
         .client_started(Some(&"config".to_string()), Some(&"config".to_string()));
 
-    if init.current_file.is_some() {
-        let file = init.current_file.clone().as_ref().unwrap().to_owned();
-        let path_str = format!("{}", file.display());
-        client.lock().unwrap().new_view(path_str, move |_| {});
-    }
-
     if !init.theme_name.is_empty() {
         client.lock().unwrap().send_notification(
             "set_theme",
@@ -176,6 +221,7 @@ pub fn main() {
             "font_face": "Inconsolata",
             "font_size": 14.0,
             "use_tab_stops": true,
+            // views turn soft wrap on for themselves, see `AppState::toggle_word_wrap`
             "word_wrap": false,
             "line_ending": LINE_ENDING,
         }),
@@ -0,0 +1,38 @@
+use druid::{Data, Lens};
+
+use crate::{EditCommand, FindStatus};
+
+/// The contents of the find/replace bar.
+#[derive(Clone, Data, Lens, Debug, Default)]
+pub struct FindState {
+    pub visible: bool,
+    pub query: String,
+    pub replacement: String,
+    pub case_sensitive: bool,
+    pub regex: bool,
+    pub whole_words: bool,
+}
+
+impl FindState {
+    /// Whether xi-core has to search again to go from `old` to `self`.
+    pub fn query_changed(&self, old: &FindState) -> bool {
+        self.query != old.query
+            || self.case_sensitive != old.case_sensitive
+            || self.regex != old.regex
+            || self.whole_words != old.whole_words
+    }
+
+    pub fn find_command(&self) -> EditCommand {
+        EditCommand::Find {
+            chars: self.query.clone(),
+            case_sensitive: self.case_sensitive,
+            regex: self.regex,
+            whole_words: self.whole_words,
+        }
+    }
+}
+
+/// Total matches over all queries of a `find_status`.
+pub fn match_count(status: &FindStatus) -> u64 {
+    status.queries.iter().map(|query| query.matches).sum()
+}
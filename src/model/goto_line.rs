@@ -0,0 +1,40 @@
+use druid::{Data, Lens};
+
+/// The contents of the go-to-line bar.
+#[derive(Clone, Data, Lens, Debug, Default)]
+pub struct GotoLineState {
+    pub visible: bool,
+    pub query: String,
+}
+
+impl GotoLineState {
+    /// The logical line typed in, counted from 1.
+    pub fn line_num(&self) -> Option<u64> {
+        self.query
+            .trim()
+            .parse()
+            .ok()
+            .filter(|line_num| *line_num > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(query: &str) -> GotoLineState {
+        GotoLineState {
+            visible: true,
+            query: query.to_string(),
+        }
+    }
+
+    #[test]
+    fn only_line_numbers_parse() {
+        assert_eq!(Some(42), query(" 42 ").line_num());
+        assert_eq!(None, query("0").line_num());
+        assert_eq!(None, query("-3").line_num());
+        assert_eq!(None, query("12a").line_num());
+        assert_eq!(None, query("").line_num());
+    }
+}
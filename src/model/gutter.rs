@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+use std::mem;
+
+use druid::Data;
+use serde::{Deserialize, Serialize};
+
+use crate::linecache::LineShift;
+use crate::model::notification::Level;
+
+/// How the gutter numbers lines.
+#[derive(Serialize, Deserialize, Clone, Copy, Data, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LineNumbers {
+    Absolute,
+    /// The distance to the cursor's line, the cursor's line itself keeps
+    /// its absolute number.
+    Relative,
+    Hidden,
+}
+
+impl Default for LineNumbers {
+    fn default() -> Self {
+        LineNumbers::Absolute
+    }
+}
+
+impl LineNumbers {
+    /// What the gutter shows for the logical line `line_num`, with the
+    /// cursor on logical line `current`.
+    pub fn label(self, line_num: u64, current: Option<u64>) -> Option<String> {
+        match (self, current) {
+            (LineNumbers::Hidden, _) => None,
+            (LineNumbers::Relative, Some(current)) if current != line_num => {
+                Some((current.max(line_num) - current.min(line_num)).to_string())
+            }
+            _ => Some(line_num.to_string()),
+        }
+    }
+}
+
+/// What a gutter marker stands for. When a line has several, the one that
+/// sorts first is painted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MarkerKind {
+    Breakpoint,
+    Error,
+    Warning,
+    Bookmark,
+}
+
+impl MarkerKind {
+    /// The marker kind for a diagnostic of `level`, info doesn't get one.
+    pub fn diagnostic(level: Level) -> Option<MarkerKind> {
+        match level {
+            Level::Error => Some(MarkerKind::Error),
+            Level::Warning => Some(MarkerKind::Warning),
+            Level::Info => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Marker {
+    pub kind: MarkerKind,
+    /// Shown for diagnostics, breakpoints and bookmarks have none.
+    pub message: Option<String>,
+}
+
+/// The markers of one view, keyed by logical line number (1-based, like
+/// `Line::line_num`). `shift` moves them along with their lines when text
+/// is edited above them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Markers {
+    lines: BTreeMap<u64, Vec<Marker>>,
+}
+
+impl Data for Markers {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl Markers {
+    pub fn add(&mut self, line_num: u64, kind: MarkerKind, message: Option<String>) {
+        let markers = self.lines.entry(line_num).or_default();
+        markers.push(Marker { kind, message });
+        markers.sort_by_key(|marker| marker.kind);
+    }
+
+    /// Removes the markers of `kind` on a line, or adds one when it has none.
+    pub fn toggle(&mut self, line_num: u64, kind: MarkerKind) {
+        if self.get(line_num).iter().any(|marker| marker.kind == kind) {
+            self.remove(line_num, kind);
+        } else {
+            self.add(line_num, kind, None);
+        }
+    }
+
+    pub fn remove(&mut self, line_num: u64, kind: MarkerKind) {
+        if let Some(markers) = self.lines.get_mut(&line_num) {
+            markers.retain(|marker| marker.kind != kind);
+            if markers.is_empty() {
+                self.lines.remove(&line_num);
+            }
+        }
+    }
+
+    /// Drops every marker of `kind`, e.g. before a new set of diagnostics.
+    pub fn clear(&mut self, kind: MarkerKind) {
+        for markers in self.lines.values_mut() {
+            markers.retain(|marker| marker.kind != kind);
+        }
+        self.lines.retain(|_, markers| !markers.is_empty());
+    }
+
+    /// Moves the markers of the lines an update moved. The markers of the
+    /// lines it didn't move are dropped where moved lines took their place,
+    /// those lines were deleted.
+    pub fn shift(&mut self, shifts: &[LineShift]) {
+        if shifts.is_empty() {
+            return;
+        }
+        let mut kept = Vec::new();
+        for (line_num, markers) in mem::take(&mut self.lines) {
+            match shifts.iter().find_map(|shift| shift.apply(line_num)) {
+                Some(line_num) => {
+                    for marker in markers {
+                        self.add(line_num, marker.kind, marker.message);
+                    }
+                }
+                None => kept.push((line_num, markers)),
+            }
+        }
+        for (line_num, markers) in kept {
+            if !shifts.iter().any(|shift| shift.covers(line_num)) {
+                for marker in markers {
+                    self.add(line_num, marker.kind, marker.message);
+                }
+            }
+        }
+    }
+
+    /// The markers of a line, the one to paint first.
+    pub fn get(&self, line_num: u64) -> &[Marker] {
+        self.lines
+            .get(&line_num)
+            .map_or(&[], |markers| markers.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_numbers_count_from_the_cursor() {
+        let relative = LineNumbers::Relative;
+        assert_eq!(Some("3".to_string()), relative.label(7, Some(10)));
+        assert_eq!(Some("10".to_string()), relative.label(10, Some(10)));
+        assert_eq!(Some("2".to_string()), relative.label(12, Some(10)));
+        assert_eq!(Some("12".to_string()), relative.label(12, None));
+
+        assert_eq!(
+            Some("7".to_string()),
+            LineNumbers::Absolute.label(7, Some(10))
+        );
+        assert_eq!(None, LineNumbers::Hidden.label(7, Some(10)));
+    }
+
+    #[test]
+    fn markers_sort_by_kind_and_toggle() {
+        let mut markers = Markers::default();
+        markers.add(3, MarkerKind::Bookmark, None);
+        markers.add(3, MarkerKind::Error, Some("expected `;`".to_string()));
+        markers.toggle(3, MarkerKind::Breakpoint);
+
+        let kinds: Vec<MarkerKind> = markers.get(3).iter().map(|m| m.kind).collect();
+        assert_eq!(
+            vec![
+                MarkerKind::Breakpoint,
+                MarkerKind::Error,
+                MarkerKind::Bookmark
+            ],
+            kinds
+        );
+
+        markers.toggle(3, MarkerKind::Breakpoint);
+        markers.clear(MarkerKind::Error);
+        markers.remove(3, MarkerKind::Bookmark);
+        assert!(markers.get(3).is_empty());
+        assert_eq!(Markers::default(), markers);
+    }
+
+    #[test]
+    fn markers_move_with_their_lines() {
+        let mut markers = Markers::default();
+        markers.add(2, MarkerKind::Bookmark, None);
+        markers.add(5, MarkerKind::Breakpoint, None);
+        markers.add(9, MarkerKind::Bookmark, None);
+
+        // two lines inserted above line 5
+        markers.shift(&[LineShift {
+            first: 5,
+            last: None,
+            delta: 2,
+        }]);
+        assert_eq!(MarkerKind::Bookmark, markers.get(2)[0].kind);
+        assert_eq!(MarkerKind::Breakpoint, markers.get(7)[0].kind);
+        assert_eq!(MarkerKind::Bookmark, markers.get(11)[0].kind);
+        assert!(markers.get(5).is_empty());
+
+        // lines 2 to 4 deleted, their marker goes with them
+        markers.shift(&[LineShift {
+            first: 5,
+            last: None,
+            delta: -3,
+        }]);
+        assert!(markers.get(2).is_empty());
+        assert_eq!(MarkerKind::Breakpoint, markers.get(4)[0].kind);
+        assert_eq!(MarkerKind::Bookmark, markers.get(8)[0].kind);
+    }
+}
@@ -1 +1,7 @@
 pub mod file_tree;
+pub mod find;
+pub mod goto_line;
+pub mod gutter;
+pub mod notification;
+pub mod plugin;
+pub mod view;
@@ -0,0 +1,140 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use druid::Data;
+
+/// How many entries the event log keeps before dropping the oldest.
+const MAX_ENTRIES: usize = 500;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Level {
+    /// Info only goes to the event log, warnings and errors also pop up a toast.
+    pub fn toasts(self) -> bool {
+        self != Level::Info
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::Info => "info",
+            Level::Warning => "warning",
+            Level::Error => "error",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Notification {
+    pub id: u64,
+    pub level: Level,
+    pub message: String,
+    pub time: SystemTime,
+}
+
+impl Notification {
+    /// `HH:MM:SS` in UTC.
+    pub fn time_of_day(&self) -> String {
+        let secs = self
+            .time
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs())
+            .unwrap_or(0)
+            % 86400;
+        format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    }
+}
+
+/// The event log, and which of its entries are still shown as toasts.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Notifications {
+    /// Oldest first.
+    pub entries: Vec<Notification>,
+    pub toasts: Vec<u64>,
+    next_id: u64,
+}
+
+impl Data for Notifications {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl Notifications {
+    pub fn push(&mut self, level: Level, message: impl Into<String>) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.entries.push(Notification {
+            id,
+            level,
+            message: message.into(),
+            time: SystemTime::now(),
+        });
+        if level.toasts() {
+            self.toasts.push(id);
+        }
+
+        if self.entries.len() > MAX_ENTRIES {
+            let dropped = self.entries.remove(0);
+            self.dismiss(dropped.id);
+        }
+        id
+    }
+
+    pub fn dismiss(&mut self, id: u64) {
+        self.toasts.retain(|toast| *toast != id);
+    }
+
+    /// Empties the event log, along with any toast still showing.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.toasts.clear();
+    }
+
+    pub fn toast_entries(&self) -> impl Iterator<Item = &Notification> {
+        self.entries
+            .iter()
+            .filter(move |entry| self.toasts.contains(&entry.id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_warnings_and_errors_toast() {
+        let mut notifications = Notifications::default();
+        notifications.push(Level::Info, "saved");
+        let warning = notifications.push(Level::Warning, "plugin crashed");
+        let error = notifications.push(Level::Error, "save failed");
+
+        assert_eq!(3, notifications.entries.len());
+        assert_eq!(vec![warning, error], notifications.toasts);
+
+        notifications.dismiss(warning);
+        let toasts: Vec<&str> = notifications
+            .toast_entries()
+            .map(|entry| entry.message.as_str())
+            .collect();
+        assert_eq!(vec!["save failed"], toasts);
+        assert_eq!(3, notifications.entries.len());
+    }
+
+    #[test]
+    fn log_is_bounded() {
+        let mut notifications = Notifications::default();
+        let first = notifications.push(Level::Error, "first");
+        for i in 0..MAX_ENTRIES {
+            notifications.push(Level::Info, format!("{}", i));
+        }
+
+        assert_eq!(MAX_ENTRIES, notifications.entries.len());
+        assert_eq!("0", notifications.entries[0].message);
+        assert!(!notifications.toasts.contains(&first));
+    }
+}
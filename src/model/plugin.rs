@@ -0,0 +1,111 @@
+use druid::Data;
+
+use crate::rpc::structs::{Plugin, PluginCommand};
+
+/// One xi plugin as seen by a view.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PluginState {
+    pub name: String,
+    pub running: bool,
+    /// What the plugin advertised through `update_cmds`.
+    pub cmds: Vec<PluginCommand>,
+}
+
+/// The plugins of a view, in the order xi-core listed them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Plugins(pub Vec<PluginState>);
+
+impl Data for Plugins {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl Plugins {
+    /// Replaces the list from `available_plugins`, keeping known commands.
+    pub fn set_available(&mut self, plugins: &[Plugin]) {
+        let old = std::mem::take(&mut self.0);
+        self.0 = plugins
+            .iter()
+            .map(|plugin| PluginState {
+                name: plugin.name.clone(),
+                running: plugin.running,
+                cmds: old
+                    .iter()
+                    .find(|p| p.name == plugin.name)
+                    .map(|p| p.cmds.clone())
+                    .unwrap_or_default(),
+            })
+            .collect();
+    }
+
+    pub fn set_running(&mut self, name: &str, running: bool) {
+        let plugin = self.get_or_insert(name);
+        plugin.running = running;
+        if !running {
+            plugin.cmds.clear();
+        }
+    }
+
+    pub fn set_cmds(&mut self, name: &str, cmds: &[PluginCommand]) {
+        self.get_or_insert(name).cmds = cmds.to_vec();
+    }
+
+    fn get_or_insert(&mut self, name: &str) -> &mut PluginState {
+        match self.0.iter().position(|p| p.name == name) {
+            Some(index) => &mut self.0[index],
+            None => {
+                self.0.push(PluginState {
+                    name: name.to_string(),
+                    running: false,
+                    cmds: vec![],
+                });
+                self.0.last_mut().unwrap()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn command(title: &str) -> PluginCommand {
+        serde_json::from_value(json!({
+            "title": title,
+            "description": "",
+            "rpc_cmd": { "rpc_type": "notification", "method": title, "params": {} },
+            "args": []
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn track_plugin_lifecycle() {
+        let mut plugins = Plugins::default();
+        plugins.set_available(&[Plugin {
+            name: "syntect".to_string(),
+            running: true,
+        }]);
+        plugins.set_cmds("syntect", &[command("reload")]);
+        plugins.set_running("lsp", true);
+        assert_eq!(2, plugins.0.len());
+
+        plugins.set_available(&[
+            Plugin {
+                name: "syntect".to_string(),
+                running: true,
+            },
+            Plugin {
+                name: "lsp".to_string(),
+                running: true,
+            },
+        ]);
+        assert_eq!(vec![command("reload")], plugins.0[0].cmds);
+
+        plugins.set_running("syntect", false);
+        assert!(!plugins.0[0].running);
+        assert!(plugins.0[0].cmds.is_empty());
+    }
+}
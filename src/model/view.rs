@@ -0,0 +1,328 @@
+use std::collections::{BTreeSet, HashMap};
+
+use druid::{Data, Lens};
+
+use crate::linecache::LineCache;
+use crate::model::gutter::Markers;
+use crate::model::plugin::Plugins;
+use crate::{ConfigChanges, FindStatus, ReplaceStatus, Update, ViewId};
+
+/// Everything we keep about one xi-core view.
+#[derive(Clone, Data, Lens, Debug)]
+pub struct ViewState {
+    pub id: ViewId,
+    pub file_path: Option<String>,
+    /// Compared by its revision, so `same` stays cheap however long the
+    /// file is.
+    pub line_cache: LineCache,
+    /// Line and column of the cursor, from the last `scroll_to`.
+    pub cursor: (u64, u64),
+    pub pristine: bool,
+    /// As last reported by `language_changed`.
+    pub language: Option<String>,
+    #[data(same_fn = "PartialEq::eq")]
+    pub config: ConfigChanges,
+    #[data(same_fn = "PartialEq::eq")]
+    pub find_status: Option<FindStatus>,
+    #[data(same_fn = "PartialEq::eq")]
+    pub replace_status: Option<ReplaceStatus>,
+    pub plugins: Plugins,
+    /// Breakpoints, bookmarks and diagnostics shown in the gutter.
+    pub markers: Markers,
+    /// A go-to-line sent to an estimated row, as the logical line's first
+    /// row wasn't cached: the line and the row.
+    pub pending_goto: Option<(u64, u64)>,
+}
+
+impl ViewState {
+    pub fn new(id: ViewId) -> ViewState {
+        ViewState {
+            id,
+            file_path: None,
+            line_cache: LineCache::new(),
+            cursor: (0, 0),
+            pristine: true,
+            language: None,
+            config: Default::default(),
+            find_status: None,
+            replace_status: None,
+            plugins: Default::default(),
+            markers: Default::default(),
+            pending_goto: None,
+        }
+    }
+
+    pub fn update(&mut self, update: Update) {
+        self.pristine = update.pristine;
+        let shifts = self.line_cache.update(update);
+        self.markers.shift(&shifts);
+    }
+
+    /// The row to send xi-core's `goto_line` for logical line `line_num`.
+    /// With wrapped lines in between it may only be estimated, then it's
+    /// checked by `correct_goto` once the rows around it arrive.
+    pub fn goto_row(&mut self, line_num: u64) -> u64 {
+        match self.line_cache.row_of_line(line_num) {
+            Some(row) => {
+                self.pending_goto = None;
+                row
+            }
+            None => {
+                let row = self.line_cache.estimate_row(line_num);
+                self.pending_goto = Some((line_num, row));
+                row
+            }
+        }
+    }
+
+    /// The row to go to instead of the estimate of a pending go-to-line,
+    /// once the update with the rows around it arrived and they tell the
+    /// estimate was off. Without the line's own row, the estimate improves
+    /// with the rows that came in and stays pending.
+    pub fn correct_goto(&mut self) -> Option<u64> {
+        let (line_num, row) = self.pending_goto?;
+        self.line_cache.get_line(row)?;
+        self.pending_goto = None;
+        let actual = match self.line_cache.row_of_line(line_num) {
+            Some(actual) => actual,
+            None => {
+                let estimate = self.line_cache.estimate_row(line_num);
+                if estimate == row {
+                    return None;
+                }
+                self.pending_goto = Some((line_num, estimate));
+                estimate
+            }
+        };
+        Some(actual).filter(|actual| *actual != row)
+    }
+
+    /// Style ids referenced by the cached lines.
+    pub fn used_styles(&self) -> BTreeSet<u64> {
+        self.line_cache
+            .lines()
+            .flat_map(|line| line.styles.iter().map(|style| style.style_id))
+            .collect()
+    }
+
+    /// The whole buffer, when no line of it is missing from the cache.
+    pub fn text(&self) -> Option<String> {
+        let cache = &self.line_cache;
+        if !cache.is_complete() {
+            return None;
+        }
+        Some(cache.lines().map(|line| line.text.as_str()).collect())
+    }
+}
+
+/// The open views, keyed by the id xi-core gave them.
+#[derive(Clone, Debug, Default)]
+pub struct ViewCore {
+    pub focused: Option<ViewId>,
+    pub views: HashMap<ViewId, ViewState>,
+}
+
+impl Data for ViewCore {
+    /// Each view compares by the revision of its line cache and the rest of
+    /// its state, none of which means walking the lines.
+    fn same(&self, other: &Self) -> bool {
+        self.focused == other.focused
+            && self.views.len() == other.views.len()
+            && self
+                .views
+                .iter()
+                .all(|(id, view)| other.views.get(id).map_or(false, |other| view.same(other)))
+    }
+}
+
+impl ViewCore {
+    /// Registers the view xi-core opened for `file_path` and focuses it.
+    pub fn open(&mut self, id: ViewId, file_path: String) -> &mut ViewState {
+        self.focused = Some(id);
+        let view = self.entry(id);
+        view.file_path = Some(file_path);
+        view
+    }
+
+    pub fn get(&self, id: ViewId) -> Option<&ViewState> {
+        self.views.get(&id)
+    }
+
+    /// Returns the view, creating it when xi-core talks about one we haven't
+    /// registered yet: updates may overtake the `new_view` response.
+    pub fn entry(&mut self, id: ViewId) -> &mut ViewState {
+        self.views.entry(id).or_insert_with(|| ViewState::new(id))
+    }
+
+    pub fn focused(&self) -> Option<&ViewState> {
+        self.focused.and_then(|id| self.views.get(&id))
+    }
+
+    /// Whether the focused view is the same one as in `other`, unchanged.
+    /// Changes to views in the background don't count.
+    pub fn same_focused(&self, other: &ViewCore) -> bool {
+        match (self.focused(), other.focused()) {
+            (Some(view), Some(other)) => view.same(other),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::gutter::MarkerKind;
+    use serde_json::json;
+
+    fn update(view_id: &str, text: &str) -> Update {
+        serde_json::from_value(json!({
+            "view_id": view_id,
+            "update": {
+                "ops": [{ "op": "ins", "n": 1, "lines": [{ "text": text, "styles": [0, 2, 3] }] }],
+                "pristine": false
+            }
+        }))
+        .unwrap()
+    }
+
+    fn first_line(views: &ViewCore, id: ViewId) -> &str {
+        let line = views.get(id).unwrap().line_cache.get_line(0);
+        line.unwrap().text.as_str()
+    }
+
+    #[test]
+    fn update_before_open_keeps_lines() {
+        let mut views = ViewCore::default();
+        views.entry(ViewId(1)).update(update("view-id-1", "one"));
+        views.open(ViewId(1), "one.rs".to_string());
+
+        assert_eq!("one", first_line(&views, ViewId(1)));
+        let view = views.focused().unwrap();
+        assert_eq!(Some("one.rs".to_string()), view.file_path);
+        assert!(!view.pristine);
+        assert!(view.used_styles().contains(&3));
+        assert_eq!(Some("one".to_string()), view.text());
+
+        views.entry(ViewId(2)).line_cache = LineCache::from_lines(0, vec![], 1);
+        assert_eq!(None, views.get(ViewId(2)).unwrap().text());
+    }
+
+    #[test]
+    fn views_are_independent() {
+        let mut views = ViewCore::default();
+        views.open(ViewId(1), "one.rs".to_string());
+        views.open(ViewId(2), "two.rs".to_string());
+        views.entry(ViewId(1)).update(update("view-id-1", "one"));
+        views.entry(ViewId(2)).update(update("view-id-2", "two"));
+
+        assert_eq!(Some(ViewId(2)), views.focused);
+        assert_eq!("one", first_line(&views, ViewId(1)));
+        assert_eq!("two", first_line(&views, ViewId(2)));
+    }
+
+    #[test]
+    fn only_changed_views_differ() {
+        let mut views = ViewCore::default();
+        views.open(ViewId(1), "one.rs".to_string());
+        views.open(ViewId(2), "two.rs".to_string());
+        let before = views.clone();
+        assert!(views.same(&before));
+
+        views.entry(ViewId(1)).update(update("view-id-1", "one"));
+        assert!(!views.same(&before));
+        // the focused view is still the second one
+        assert!(views.same_focused(&before));
+
+        let before = views.clone();
+        views.entry(ViewId(2)).update(update("view-id-2", "two"));
+        assert!(!views.same_focused(&before));
+        let before = views.clone();
+        views.focused = Some(ViewId(1));
+        assert!(!views.same_focused(&before));
+    }
+
+    #[test]
+    fn bookmarks_move_with_lines_inserted_above() {
+        let mut view = ViewState::new(ViewId(1));
+        view.update(
+            serde_json::from_value(json!({
+                "view_id": "view-id-1",
+                "update": { "ops": [{ "op": "ins", "n": 3, "lines": [
+                    { "text": "one\n", "ln": 1 },
+                    { "text": "two\n", "ln": 2 },
+                    { "text": "three\n", "ln": 3 }
+                ] }], "pristine": true }
+            }))
+            .unwrap(),
+        );
+        view.markers.toggle(2, MarkerKind::Bookmark);
+
+        // two lines typed above the first one
+        view.update(
+            serde_json::from_value(json!({
+                "view_id": "view-id-1",
+                "update": { "ops": [
+                    { "op": "ins", "n": 2, "lines": [
+                        { "text": "new\n", "ln": 1 },
+                        { "text": "lines\n", "ln": 2 }
+                    ] },
+                    { "op": "copy", "n": 3, "ln": 3 }
+                ], "pristine": false }
+            }))
+            .unwrap(),
+        );
+        assert!(view.markers.get(2).is_empty());
+        assert_eq!(MarkerKind::Bookmark, view.markers.get(4)[0].kind);
+        assert_eq!("two\n", view.line_cache.get_line(3).unwrap().text);
+    }
+
+    #[test]
+    fn goto_line_is_corrected_once_its_rows_arrive() {
+        let mut view = ViewState::new(ViewId(1));
+        view.update(
+            serde_json::from_value(json!({
+                "view_id": "view-id-1",
+                "update": { "ops": [
+                    { "op": "ins", "n": 1, "lines": [{ "text": "one\n", "ln": 1 }] },
+                    { "op": "invalidate", "n": 19 }
+                ], "pristine": true }
+            }))
+            .unwrap(),
+        );
+
+        // counted from line 1 as if nothing wrapped
+        assert_eq!(4, view.goto_row(5));
+        assert_eq!(None, view.correct_goto());
+        assert_eq!(Some((5, 4)), view.pending_goto);
+
+        // line 2 wraps over three rows, so line 5 starts on row 6
+        view.update(
+            serde_json::from_value(json!({
+                "view_id": "view-id-1",
+                "update": { "ops": [
+                    { "op": "copy", "n": 1, "ln": 1 },
+                    { "op": "skip", "n": 7 },
+                    { "op": "ins", "n": 7, "lines": [
+                        { "text": "two ", "ln": 2 },
+                        { "text": "wrapped " },
+                        { "text": "twice\n" },
+                        { "text": "three\n", "ln": 3 },
+                        { "text": "four\n", "ln": 4 },
+                        { "text": "five\n", "ln": 5 },
+                        { "text": "six\n", "ln": 6 }
+                    ] },
+                    { "op": "invalidate", "n": 12 }
+                ], "pristine": true }
+            }))
+            .unwrap(),
+        );
+        assert_eq!(Some(6), view.correct_goto());
+        assert_eq!(None, view.pending_goto);
+        assert_eq!(None, view.correct_goto());
+
+        // a cached line needs no correction
+        assert_eq!(5, view.goto_row(4));
+        assert_eq!(None, view.pending_goto);
+    }
+}
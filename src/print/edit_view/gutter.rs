@@ -0,0 +1,105 @@
+use druid::kurbo::Circle;
+use druid::{Color, PaintCtx, Point, Rect, RenderContext};
+use druid_shell::piet::{TextLayout, TextLayoutBuilder};
+use piet_common::Text;
+
+use super::EditorFont;
+use crate::model::gutter::{LineNumbers, MarkerKind};
+use crate::model::view::ViewState;
+
+/// The column markers are painted in, left of the numbers.
+pub const MARKER_WIDTH: f64 = 14.0;
+const NUMBER_PAD: f64 = 10.0;
+
+pub struct GutterColors {
+    pub background: Color,
+    pub foreground: Color,
+    /// The number of the cursor's line.
+    pub current: Color,
+}
+
+/// Wide enough for the digits of the last line number, which is at most
+/// the number of visual lines.
+pub fn width(mode: LineNumbers, lines: u64, digit_width: f64) -> f64 {
+    if mode == LineNumbers::Hidden {
+        return MARKER_WIDTH;
+    }
+    let digits = lines.to_string().len().max(2);
+    MARKER_WIDTH + digits as f64 * digit_width + NUMBER_PAD
+}
+
+fn marker_color(kind: MarkerKind) -> Color {
+    match kind {
+        MarkerKind::Breakpoint => Color::rgb8(0xd0, 0x3a, 0x3a),
+        MarkerKind::Error => Color::rgb8(0xe0, 0x50, 0x50),
+        MarkerKind::Warning => Color::rgb8(0xd8, 0xa0, 0x20),
+        MarkerKind::Bookmark => Color::rgb8(0x3c, 0x7e, 0xd0),
+    }
+}
+
+fn paint_marker(ctx: &mut PaintCtx, kind: MarkerKind, y: f64, line_height: f64) {
+    let center = Point::new(MARKER_WIDTH / 2.0, y + line_height / 2.0);
+    let radius = (line_height / 2.0 - 2.0)
+        .min(MARKER_WIDTH / 2.0 - 2.0)
+        .max(2.0);
+    let color = marker_color(kind);
+    match kind {
+        MarkerKind::Breakpoint => ctx.fill(Circle::new(center, radius), &color),
+        MarkerKind::Error | MarkerKind::Warning => {
+            let rect = Rect::from_center_size(center, (radius * 1.4, radius * 1.4));
+            ctx.fill(rect.to_rounded_rect(1.0), &color)
+        }
+        MarkerKind::Bookmark => {
+            let rect = Rect::from_center_size(center, (radius * 1.2, radius * 2.0));
+            ctx.fill(rect, &color)
+        }
+    }
+}
+
+/// Paints the gutter of visual lines `first..last`, `offset_y` being how far
+/// the view is scrolled down.
+#[allow(clippy::too_many_arguments)]
+pub fn paint(
+    ctx: &mut PaintCtx,
+    font: &EditorFont,
+    view: &ViewState,
+    mode: LineNumbers,
+    (first, last): (u64, u64),
+    offset_y: f64,
+    width: f64,
+    colors: &GutterColors,
+) {
+    let size = ctx.size();
+    ctx.fill(Rect::new(0.0, 0.0, width, size.height), &colors.background);
+
+    let line_height = font.line_height;
+    let current = view.line_cache.line_num_at(view.cursor.0);
+    for n in first..last {
+        let line_num = match view.line_cache.get_line(n).and_then(|line| line.line_num) {
+            Some(line_num) => line_num,
+            None => continue,
+        };
+        let y = n as f64 * line_height - offset_y;
+
+        if let Some(marker) = view.markers.get(line_num).first() {
+            paint_marker(ctx, marker.kind, y, line_height);
+        }
+
+        if let Some(label) = mode.label(line_num, current) {
+            let color = if current == Some(line_num) {
+                &colors.current
+            } else {
+                &colors.foreground
+            };
+            let layout = ctx
+                .text()
+                .new_text_layout(label)
+                .font(font.family.clone(), font.config.font_size)
+                .text_color(color.clone())
+                .build()
+                .unwrap();
+            let x = width - NUMBER_PAD / 2.0 - layout.size().width;
+            ctx.draw_text(&layout, (x, y));
+        }
+    }
+}
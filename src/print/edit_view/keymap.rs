@@ -0,0 +1,149 @@
+use druid::{KbKey, KeyEvent};
+
+use crate::EditCommand;
+use crate::EditCommand::*;
+
+/// Maps a key press onto the xi edit command it stands for, following the
+/// platform's conventions for word and line movement. Returns `None` for
+/// keys the editor leaves to menus and other widgets.
+pub fn edit_command(event: &KeyEvent) -> Option<EditCommand> {
+    let mods = event.mods;
+    let shift = mods.shift();
+    let macos = cfg!(target_os = "macos");
+    // Cmd on macOS, Ctrl elsewhere
+    let cmd = if macos { mods.meta() } else { mods.ctrl() };
+    // Option on macOS, Ctrl elsewhere
+    let word = if macos { mods.alt() } else { mods.ctrl() };
+    // Cmd+arrows go to the line or document ends on macOS
+    let edge = macos && mods.meta();
+    let pick = |plain: EditCommand, extend: EditCommand| if shift { extend } else { plain };
+
+    let command = match &event.key {
+        KbKey::Character(c) if cmd => match c.to_lowercase().as_str() {
+            "a" => SelectAll,
+            "z" if shift => Redo,
+            "z" => Undo,
+            "y" if !macos => Redo,
+            _ => return None,
+        },
+        KbKey::Character(chars) if !mods.ctrl() && !mods.meta() => Insert {
+            chars: chars.clone(),
+        },
+        KbKey::Enter => InsertNewline,
+        KbKey::Tab if !shift => InsertTab,
+        KbKey::Backspace if edge => DeleteToBeginningOfLine,
+        KbKey::Backspace if word => DeleteWordBackward,
+        KbKey::Backspace => DeleteBackward,
+        KbKey::Delete if word => DeleteWordForward,
+        KbKey::Delete => DeleteForward,
+        KbKey::ArrowLeft if edge => {
+            pick(MoveToLeftEndOfLine, MoveToLeftEndOfLineAndModifySelection)
+        }
+        KbKey::ArrowLeft if word => pick(MoveWordLeft, MoveWordLeftAndModifySelection),
+        KbKey::ArrowLeft => pick(MoveLeft, MoveLeftAndModifySelection),
+        KbKey::ArrowRight if edge => {
+            pick(MoveToRightEndOfLine, MoveToRightEndOfLineAndModifySelection)
+        }
+        KbKey::ArrowRight if word => pick(MoveWordRight, MoveWordRightAndModifySelection),
+        KbKey::ArrowRight => pick(MoveRight, MoveRightAndModifySelection),
+        KbKey::ArrowUp if edge => pick(
+            MoveToBeginningOfDocument,
+            MoveToBeginningOfDocumentAndModifySelection,
+        ),
+        KbKey::ArrowUp => pick(MoveUp, MoveUpAndModifySelection),
+        KbKey::ArrowDown if edge => {
+            pick(MoveToEndOfDocument, MoveToEndOfDocumentAndModifySelection)
+        }
+        KbKey::ArrowDown => pick(MoveDown, MoveDownAndModifySelection),
+        KbKey::Home if cmd => pick(
+            MoveToBeginningOfDocument,
+            MoveToBeginningOfDocumentAndModifySelection,
+        ),
+        KbKey::Home => pick(MoveToLeftEndOfLine, MoveToLeftEndOfLineAndModifySelection),
+        KbKey::End if cmd => pick(MoveToEndOfDocument, MoveToEndOfDocumentAndModifySelection),
+        KbKey::End => pick(MoveToRightEndOfLine, MoveToRightEndOfLineAndModifySelection),
+        KbKey::PageUp => pick(ScrollPageUp, PageUpAndModifySelection),
+        KbKey::PageDown => pick(ScrollPageDown, PageDownAndModifySelection),
+        KbKey::Escape => CancelOperation,
+        _ => return None,
+    };
+    Some(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use druid::Modifiers;
+
+    fn key(mods: Modifiers, key: KbKey) -> KeyEvent {
+        KeyEvent {
+            key,
+            mods,
+            ..Default::default()
+        }
+    }
+
+    fn typed(mods: Modifiers, c: &str) -> KeyEvent {
+        key(mods, KbKey::Character(c.to_string()))
+    }
+
+    #[cfg(target_os = "macos")]
+    const CMD: Modifiers = Modifiers::META;
+    #[cfg(not(target_os = "macos"))]
+    const CMD: Modifiers = Modifiers::CONTROL;
+
+    #[test]
+    fn typing_inserts() {
+        let chars = "é".to_string();
+        assert_eq!(
+            Some(Insert { chars }),
+            edit_command(&typed(Modifiers::empty(), "é"))
+        );
+        let chars = "A".to_string();
+        assert_eq!(
+            Some(Insert { chars }),
+            edit_command(&typed(Modifiers::SHIFT, "A"))
+        );
+        assert_eq!(
+            Some(InsertNewline),
+            edit_command(&key(Modifiers::empty(), KbKey::Enter))
+        );
+    }
+
+    #[test]
+    fn shift_extends_the_selection() {
+        assert_eq!(
+            Some(MoveLeft),
+            edit_command(&key(Modifiers::empty(), KbKey::ArrowLeft))
+        );
+        assert_eq!(
+            Some(MoveDownAndModifySelection),
+            edit_command(&key(Modifiers::SHIFT, KbKey::ArrowDown))
+        );
+        assert_eq!(
+            Some(PageUpAndModifySelection),
+            edit_command(&key(Modifiers::SHIFT, KbKey::PageUp))
+        );
+    }
+
+    #[test]
+    fn shortcuts_are_not_typed() {
+        assert_eq!(Some(Undo), edit_command(&typed(CMD, "z")));
+        assert_eq!(
+            Some(Redo),
+            edit_command(&typed(CMD | Modifiers::SHIFT, "Z"))
+        );
+        assert_eq!(Some(SelectAll), edit_command(&typed(CMD, "a")));
+        // left to the menus
+        assert_eq!(None, edit_command(&typed(CMD, "f")));
+        assert_eq!(None, edit_command(&key(Modifiers::empty(), KbKey::F1)));
+    }
+
+    #[test]
+    fn clipboard_shortcuts_are_left_to_the_edit_menu() {
+        // the menu's cut, copy and paste hotkeys turn these into commands
+        for c in &["x", "c", "v"] {
+            assert_eq!(None, edit_command(&typed(CMD, c)));
+        }
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use druid::Color;
+use druid_shell::piet::PietText;
+
+use super::line_layout::LineLayout;
+use super::{EditorConfig, EditorFont};
+use crate::linecache::Line;
+use crate::model::view::ViewState;
+use crate::{Style, ViewId};
+
+/// Hashes a line by what its layout is built from: the text and the style
+/// spans. The cursors don't change the layout and are left out. Two lines
+/// may share a key, so a hit is checked with `LineLayout::is_of`.
+pub fn line_key(line: &Line) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    line.text.hash(&mut hasher);
+    for style in line.styles.iter() {
+        (style.offset, style.length, style.style_id).hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// What every cached layout was built with.
+#[derive(PartialEq)]
+struct LayoutContext {
+    config: EditorConfig,
+    foreground: Color,
+    styles: HashMap<usize, Style>,
+}
+
+/// Keeps the layouts of the cached lines, so painting again after a scroll
+/// or a caret blink only lays out the lines that changed.
+#[derive(Default)]
+pub struct LayoutCache {
+    context: Option<LayoutContext>,
+    layouts: HashMap<u64, LineLayout>,
+    /// The view and revision the layouts were last pruned for.
+    pruned: Option<(ViewId, u64)>,
+}
+
+impl LayoutCache {
+    /// Drops every layout when the font, the theme or the style definitions
+    /// changed. Once the lines changed, only the layouts of the `visible`
+    /// lines that are still there are kept.
+    pub fn sync(
+        &mut self,
+        font: &EditorFont,
+        foreground: &Color,
+        styles: &HashMap<usize, Style>,
+        view: &ViewState,
+        (first, last): (u64, u64),
+    ) {
+        let stale = match &self.context {
+            Some(context) => {
+                context.config != font.config
+                    || &context.foreground != foreground
+                    || &context.styles != styles
+            }
+            None => true,
+        };
+        if stale {
+            self.layouts.clear();
+            self.context = Some(LayoutContext {
+                config: font.config.clone(),
+                foreground: foreground.clone(),
+                styles: styles.clone(),
+            });
+        }
+
+        if self.pruned != Some((view.id, view.line_cache.revision)) {
+            self.pruned = Some((view.id, view.line_cache.revision));
+            let live: HashSet<u64> = (first..last)
+                .filter_map(|n| view.line_cache.get_line(n))
+                .map(line_key)
+                .collect();
+            self.layouts.retain(|key, _| live.contains(key));
+        }
+    }
+
+    /// The layout of `line`, built when it isn't cached yet.
+    pub fn layout(
+        &mut self,
+        text: &mut PietText,
+        font: &EditorFont,
+        line: &Line,
+        styles: &HashMap<usize, Style>,
+        foreground: &Color,
+    ) -> &LineLayout {
+        let key = line_key(line);
+        let cached = self
+            .layouts
+            .get(&key)
+            .map_or(false, |layout| layout.is_of(line));
+        if !cached {
+            let layout = LineLayout::new(text, font, line, styles, foreground);
+            self.layouts.insert(key, layout);
+        }
+        &self.layouts[&key]
+    }
+
+    pub fn get(&self, line: &Line) -> Option<&LineLayout> {
+        self.layouts
+            .get(&line_key(line))
+            .filter(|layout| layout.is_of(line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use piet_common::{Device, RenderContext};
+    use serde_json::json;
+
+    use super::*;
+    use crate::{ConfigChanges, StyleDef, Update};
+
+    fn line(text: &str, style_id: u64, cursor: Vec<u64>) -> Line {
+        Line {
+            text: text.to_string(),
+            cursor,
+            styles: vec![StyleDef {
+                offset: 0,
+                length: 2,
+                style_id,
+            }],
+            line_num: Some(1),
+        }
+    }
+
+    #[test]
+    fn keys_follow_text_and_styles_only() {
+        let key = line_key(&line("fn main", 2, vec![]));
+        assert_eq!(key, line_key(&line("fn main", 2, vec![3])));
+        assert_ne!(key, line_key(&line("fn main", 3, vec![])));
+        assert_ne!(key, line_key(&line("fn mains", 2, vec![])));
+    }
+
+    fn with_text(f: impl FnOnce(&mut PietText)) {
+        let mut device = Device::new().unwrap();
+        let mut target = device.bitmap_target(1, 1, 1.0).unwrap();
+        let mut rc = target.render_context();
+        f(rc.text());
+        rc.finish().unwrap();
+    }
+
+    fn editor_font(text: &mut PietText, config: ConfigChanges) -> EditorFont {
+        EditorFont::new(text, EditorConfig::new(&config))
+    }
+
+    fn view(lines: &[&str]) -> ViewState {
+        let mut view = ViewState::new(ViewId(1));
+        let lines: Vec<_> = lines
+            .iter()
+            .enumerate()
+            .map(|(ix, text)| json!({ "text": text, "styles": [0, 2, 1], "ln": ix + 1 }))
+            .collect();
+        view.update(update(
+            json!([{ "op": "ins", "n": lines.len(), "lines": lines }]),
+        ));
+        view
+    }
+
+    fn update(ops: serde_json::Value) -> Update {
+        serde_json::from_value(json!({
+            "view_id": "view-id-1",
+            "update": { "ops": ops, "pristine": true }
+        }))
+        .unwrap()
+    }
+
+    /// Syncs `cache` and lays out every line of `view`.
+    fn fill(
+        cache: &mut LayoutCache,
+        text: &mut PietText,
+        font: &EditorFont,
+        foreground: &Color,
+        styles: &HashMap<usize, Style>,
+        view: &ViewState,
+    ) {
+        let height = view.line_cache.height();
+        cache.sync(font, foreground, styles, view, (0, height));
+        for line in view.line_cache.lines() {
+            cache.layout(text, font, line, styles, foreground);
+        }
+    }
+
+    #[test]
+    fn sync_drops_layouts_built_with_another_context() {
+        with_text(|text| {
+            let view = view(&["fn main() {", "}"]);
+            let font = editor_font(text, ConfigChanges::default());
+            let styles = HashMap::new();
+            let mut cache = LayoutCache::default();
+            fill(&mut cache, text, &font, &Color::WHITE, &styles, &view);
+            assert_eq!(2, cache.layouts.len());
+
+            cache.sync(&font, &Color::WHITE, &styles, &view, (0, 2));
+            assert_eq!(2, cache.layouts.len());
+
+            let tabs = ConfigChanges {
+                tab_size: Some(8),
+                ..Default::default()
+            };
+            let tab_font = editor_font(text, tabs);
+            cache.sync(&tab_font, &Color::WHITE, &styles, &view, (0, 2));
+            assert!(cache.layouts.is_empty());
+
+            fill(&mut cache, text, &font, &Color::WHITE, &styles, &view);
+            let larger = ConfigChanges {
+                font_size: Some(20.0),
+                ..Default::default()
+            };
+            let larger_font = editor_font(text, larger);
+            cache.sync(&larger_font, &Color::WHITE, &styles, &view, (0, 2));
+            assert!(cache.layouts.is_empty());
+
+            fill(&mut cache, text, &font, &Color::WHITE, &styles, &view);
+            cache.sync(&font, &Color::BLACK, &styles, &view, (0, 2));
+            assert!(cache.layouts.is_empty());
+
+            fill(&mut cache, text, &font, &Color::WHITE, &styles, &view);
+            let mut restyled = HashMap::new();
+            restyled.insert(
+                1,
+                Style {
+                    id: 1,
+                    italic: Some(true),
+                    ..Default::default()
+                },
+            );
+            cache.sync(&font, &Color::WHITE, &restyled, &view, (0, 2));
+            assert!(cache.layouts.is_empty());
+        });
+    }
+
+    #[test]
+    fn sync_prunes_lost_lines_once_per_revision() {
+        with_text(|text| {
+            let mut view = view(&["fn main() {", "}"]);
+            let font = editor_font(text, ConfigChanges::default());
+            let styles = HashMap::new();
+            let mut cache = LayoutCache::default();
+            fill(&mut cache, text, &font, &Color::WHITE, &styles, &view);
+
+            // the same revision keeps layouts of lines scrolled out of view
+            cache.sync(&font, &Color::WHITE, &styles, &view, (0, 1));
+            assert_eq!(2, cache.layouts.len());
+
+            view.update(update(json!([
+                { "op": "copy", "n": 1, "ln": 1 },
+                { "op": "skip", "n": 1 },
+                { "op": "ins", "n": 1, "lines": [{ "text": "} // main", "styles": [] }] },
+            ])));
+            cache.sync(&font, &Color::WHITE, &styles, &view, (0, 2));
+            assert_eq!(1, cache.layouts.len());
+            assert!(cache.get(view.line_cache.get_line(0).unwrap()).is_some());
+            assert!(cache.get(view.line_cache.get_line(1).unwrap()).is_none());
+        });
+    }
+
+    #[test]
+    fn a_shared_key_is_not_a_hit() {
+        with_text(|text| {
+            let font = editor_font(text, ConfigChanges::default());
+            let styles = HashMap::new();
+            let (main, other) = (line("fn main", 2, vec![]), line("fn other", 2, vec![]));
+            let mut cache = LayoutCache::default();
+            let layout = LineLayout::new(text, &font, &main, &styles, &Color::WHITE);
+            cache.layouts.insert(line_key(&other), layout);
+
+            assert!(cache.get(&other).is_none());
+            let layout = cache.layout(text, &font, &other, &styles, &Color::WHITE);
+            assert!(layout.is_of(&other));
+        });
+    }
+}
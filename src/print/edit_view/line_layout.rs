@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use druid::{Color, FontWeight, Point, Rect};
+use druid_shell::piet::{
+    FontStyle, PietText, PietTextLayout, TextAttribute, TextLayout, TextLayoutBuilder,
+};
+use piet_common::Text;
+
+use super::EditorFont;
+use crate::linecache::Line;
+use crate::support::grapheme::snap_to_grapheme;
+use crate::support::tabs::ExpandedLine;
+use crate::theme;
+use crate::Style;
+
+/// Resolves the relative offsets of a line's style spans into
+/// `(start, end, style_id)` byte ranges.
+pub fn style_spans(line: &Line) -> Vec<(usize, usize, u64)> {
+    let mut end = 0;
+    line.styles
+        .iter()
+        .map(|style| {
+            let start = (end as i64 + style.offset).max(0) as usize;
+            end = start + style.length as usize;
+            (start, end, style.style_id)
+        })
+        .collect()
+}
+
+/// A cached line laid out with its tabs expanded and its styles applied,
+/// able to map xi's byte offsets into the layout.
+pub struct LineLayout {
+    pub layout: PietTextLayout,
+    expanded: ExpandedLine,
+    text: String,
+    spans: Vec<(usize, usize, u64)>,
+}
+
+impl LineLayout {
+    pub fn new(
+        text: &mut PietText,
+        font: &EditorFont,
+        line: &Line,
+        styles: &HashMap<usize, Style>,
+        foreground: &Color,
+    ) -> LineLayout {
+        let expanded = ExpandedLine::new(&line.text, font.config.tab_size);
+        let mut layout = text
+            .new_text_layout(expanded.text.clone())
+            .font(font.family.clone(), font.config.font_size)
+            .text_color(foreground.clone());
+
+        let spans = style_spans(line);
+        for &(start, end, style_id) in spans.iter() {
+            let range = expanded.offset(start)..expanded.offset(end);
+            let line_style = styles.get(&(style_id as usize));
+
+            if let Some(foreground) = line_style.and_then(|s| s.fg_color) {
+                let attr = TextAttribute::TextColor(theme::color_from_u32(foreground));
+                layout = layout.range_attribute(range.clone(), attr);
+            }
+
+            if let Some(weight) = line_style.and_then(|s| s.weight) {
+                let attr = TextAttribute::Weight(FontWeight::new(weight as u16));
+                layout = layout.range_attribute(range.clone(), attr);
+            }
+
+            if let Some(italic) = line_style.and_then(|s| s.italic) {
+                if italic {
+                    let attr = TextAttribute::Style(FontStyle::Italic);
+                    layout = layout.range_attribute(range.clone(), attr);
+                }
+            }
+
+            if let Some(underline) = line_style.and_then(|s| s.underline) {
+                if underline {
+                    let attr = TextAttribute::Underline(true);
+                    layout = layout.range_attribute(range.clone(), attr);
+                }
+            }
+        }
+
+        LineLayout {
+            layout: layout.build().unwrap(),
+            expanded,
+            text: line.text.clone(),
+            spans,
+        }
+    }
+
+    /// Whether this is the layout of `line`'s text and style spans.
+    pub fn is_of(&self, line: &Line) -> bool {
+        self.text == line.text && self.spans == style_spans(line)
+    }
+
+    /// Where a caret at byte `offset` of the line goes, snapped back to the
+    /// start of the grapheme cluster the offset falls in.
+    pub fn caret_x(&self, offset: usize) -> f64 {
+        let offset = snap_to_grapheme(&self.text, offset);
+        self.layout
+            .hit_test_text_position(self.expanded.offset(offset))
+            .point
+            .x
+    }
+
+    /// The byte offset of the line closest to `x`, on a grapheme boundary.
+    pub fn offset_at(&self, x: f64) -> usize {
+        let hit = self.layout.hit_test_point(Point::new(x, 0.0));
+        let offset = self.expanded.original(hit.idx);
+        snap_to_grapheme(&self.text, offset)
+    }
+
+    /// The rects covering bytes `start..end` of the line.
+    pub fn range_rects(&self, start: usize, end: usize) -> Vec<Rect> {
+        let start = snap_to_grapheme(&self.text, start);
+        let end = snap_to_grapheme(&self.text, end);
+        let range = self.expanded.offset(start)..self.expanded.offset(end);
+        self.layout.rects_for_range(range)
+    }
+}
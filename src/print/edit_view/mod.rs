@@ -0,0 +1,690 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::app_state::AppState;
+use crate::model::gutter::MarkerKind;
+use crate::model::view::ViewState;
+use crate::support::text_measure::DEFAULT_FONT_SIZE;
+use crate::theme;
+use crate::{AnnotationType, ConfigChanges, EditCommand, Style, ViewId};
+use druid::{
+    commands, Affine, BoxConstraints, Color, Cursor, Env, Event, EventCtx, FontFamily, LayoutCtx,
+    LifeCycle, LifeCycleCtx, PaintCtx, Point, Rect, RenderContext, Size, TimerToken, UpdateCtx,
+    Vec2, Widget,
+};
+use druid_shell::piet::{PietText, TextLayout, TextLayoutBuilder};
+use piet_common::Text;
+
+use self::gutter::GutterColors;
+use self::layout_cache::LayoutCache;
+use self::line_layout::style_spans;
+use self::viewport::Viewport;
+
+mod gutter;
+mod keymap;
+mod layout_cache;
+mod line_layout;
+mod mouse;
+mod viewport;
+
+/// xi-core's default `tab_size`.
+const DEFAULT_TAB_SIZE: usize = 4;
+
+/// The part of a view's `config_changed` that affects rendering.
+#[derive(Clone, Debug, PartialEq)]
+struct EditorConfig {
+    font_face: Option<String>,
+    font_size: f64,
+    tab_size: usize,
+    word_wrap: bool,
+}
+
+impl EditorConfig {
+    fn new(config: &ConfigChanges) -> EditorConfig {
+        EditorConfig {
+            font_face: config.font_face.clone(),
+            font_size: config.font_size.map(f64::from).unwrap_or(DEFAULT_FONT_SIZE),
+            tab_size: config
+                .tab_size
+                .map_or(DEFAULT_TAB_SIZE, |size| size as usize),
+            word_wrap: config.word_wrap.unwrap_or(false),
+        }
+    }
+}
+
+/// The resolved font of an `EditorConfig`, with the line height piet
+/// reports for it.
+#[derive(Clone)]
+pub struct EditorFont {
+    config: EditorConfig,
+    family: FontFamily,
+    line_height: f64,
+    /// For sizing the gutter.
+    digit_width: f64,
+}
+
+impl EditorFont {
+    fn new(text: &mut PietText, config: EditorConfig) -> EditorFont {
+        let family = config
+            .font_face
+            .as_ref()
+            .and_then(|face| text.font_family(face))
+            .unwrap_or(FontFamily::MONOSPACE);
+
+        let line_height = text
+            .new_text_layout("Mg")
+            .font(family.clone(), config.font_size)
+            .build()
+            .ok()
+            .and_then(|layout| layout.line_metric(0))
+            .map(|metric| metric.height)
+            .unwrap_or(config.font_size * 1.2);
+        let digit_width = text
+            .new_text_layout("0")
+            .font(family.clone(), config.font_size)
+            .build()
+            .map(|layout| layout.size().width)
+            .unwrap_or(config.font_size * 0.6);
+
+        EditorFont {
+            config,
+            family,
+            line_height: line_height.ceil(),
+            digit_width,
+        }
+    }
+}
+
+pub struct EditView {
+    font: Option<EditorFont>,
+    layouts: LayoutCache,
+    viewports: HashMap<ViewId, Viewport>,
+    /// Set by a `scroll_to`, layout then brings the cursor into view.
+    reveal_cursor: bool,
+    /// Where the text starts, right of the gutter.
+    gutter_width: f64,
+    /// Whether the carets show in this phase of the blink.
+    caret_on: bool,
+    blink_timer: TimerToken,
+}
+
+impl EditView {
+    pub fn new() -> EditView {
+        EditView {
+            font: None,
+            layouts: LayoutCache::default(),
+            viewports: HashMap::new(),
+            reveal_cursor: false,
+            gutter_width: 0.0,
+            caret_on: true,
+            blink_timer: TimerToken::INVALID,
+        }
+    }
+
+    /// Shows the carets and starts the blink over, so they don't vanish
+    /// while the user is typing or moving around.
+    fn restart_blink(&mut self, request_timer: impl FnOnce(Duration) -> TimerToken) {
+        self.caret_on = true;
+        self.blink_timer = request_timer(CARET_BLINK);
+    }
+
+    /// The font for `view`, measured again only when its config changed.
+    fn font(&mut self, text: &mut PietText, view: &ViewState) -> EditorFont {
+        let config = EditorConfig::new(&view.config);
+        let stale = match &self.font {
+            Some(font) => font.config != config,
+            None => true,
+        };
+        if stale {
+            self.font = Some(EditorFont::new(text, config));
+        }
+        self.font.clone().unwrap()
+    }
+
+    /// The text color of lines without a style of their own.
+    fn foreground(data: &AppState) -> Color {
+        match &data.theme.foreground {
+            None => Color::WHITE,
+            Some(color) => theme::from_xi_color(color),
+        }
+    }
+
+    /// With `word_wrap` on, xi-core wraps lines to the width we last told it.
+    fn send_size(&self, data: &AppState, size: Size) {
+        let word_wrap = data
+            .views
+            .focused()
+            .and_then(|view| view.config.word_wrap)
+            .unwrap_or(false);
+        if word_wrap {
+            data.edit_focused(EditCommand::Resize {
+                width: size.width - self.gutter_width - LEFT_PAD,
+                height: size.height,
+            });
+        }
+    }
+
+    /// Where the cursor of the last `scroll_to` is, in content coordinates.
+    fn cursor_rect(
+        &mut self,
+        text: &mut PietText,
+        font: &EditorFont,
+        view: &ViewState,
+        styles: &HashMap<usize, Style>,
+        foreground: &Color,
+    ) -> Rect {
+        let (line, col) = view.cursor;
+        let y0 = line as f64 * font.line_height;
+        let x = match view.line_cache.get_line(line) {
+            Some(cached) => {
+                let layout = self.layouts.layout(text, font, cached, styles, foreground);
+                layout.caret_x(col as usize)
+            }
+            None => 0.0,
+        };
+        Rect::new(x, y0, x + 2.0 * LEFT_PAD, y0 + font.line_height)
+    }
+
+    /// The line and column (a byte offset into the line) under `pos`, a
+    /// point in widget coordinates. Points past the last line go to its end.
+    fn hit_test(&mut self, text: &mut PietText, data: &AppState, pos: Point) -> Option<(u64, u64)> {
+        let view = data.views.focused()?;
+        let viewport = self.viewports.get(&view.id)?.clone();
+        let offset = viewport.offset;
+        let pos = Point::new(pos.x - self.gutter_width, pos.y) + offset;
+        let font = self.font(text, view);
+        let foreground = EditView::foreground(data);
+        let visible = viewport.visible_lines(font.line_height);
+        self.layouts
+            .sync(&font, &foreground, &data.styles, view, visible);
+
+        let height = view.line_cache.height();
+        if height == 0 {
+            return Some((0, 0));
+        }
+        let line = (pos.y / font.line_height).floor().max(0.0) as u64;
+        if line >= height {
+            let col = view.line_cache.get_line(height - 1).map_or(0, |last| {
+                last.text.trim_end_matches(|c| c == '\n' || c == '\r').len()
+            });
+            return Some((height - 1, col as u64));
+        }
+        let col = match view.line_cache.get_line(line) {
+            Some(cached) => {
+                let layout = self
+                    .layouts
+                    .layout(text, &font, cached, &data.styles, &foreground);
+                layout.offset_at(pos.x - LEFT_PAD)
+            }
+            None => 0,
+        };
+        Some((line, col as u64))
+    }
+
+    /// Toggles a bookmark on the logical line under `pos`.
+    fn toggle_bookmark(&mut self, text: &mut PietText, data: &mut AppState, pos: Point) {
+        let line = match self.hit_test(text, data, pos) {
+            Some((line, _)) => line,
+            None => return,
+        };
+        let view = match data.views.focused {
+            Some(id) => data.views.entry(id),
+            None => return,
+        };
+        if let Some(line_num) = view.line_cache.line_num_at(line) {
+            view.markers.toggle(line_num, MarkerKind::Bookmark);
+        }
+    }
+
+    /// One row per cached line, wrapped or not. Lines wrapped to the width
+    /// of the editor never scroll sideways.
+    fn content_size(view: &ViewState, viewport: &Viewport, line_height: f64) -> Size {
+        let width = if view.config.word_wrap == Some(true) {
+            viewport.size.width
+        } else {
+            viewport.content_width + 2.0 * LEFT_PAD
+        };
+        Size::new(width, view.line_cache.height() as f64 * line_height)
+    }
+
+    /// Tells xi-core which lines are visible, when that changed, and asks
+    /// for the lines around them it left invalid.
+    fn sync_core(data: &AppState, view: &ViewState, viewport: &mut Viewport, line_height: f64) {
+        if let Some((first, last)) = viewport.take_changed_range(line_height) {
+            data.edit_focused(EditCommand::Scroll(first, last));
+        }
+
+        // a page either way, so scrolling doesn't run into blank lines
+        let (first, last) = viewport.visible_lines(line_height);
+        let page = last - first;
+        let missing = view
+            .line_cache
+            .get_missing(first.saturating_sub(page), last + page);
+        for (first, last) in viewport.take_unrequested(missing, view.line_cache.revision) {
+            data.edit_focused(EditCommand::RequestLines(first, last));
+        }
+    }
+
+    fn focused_viewport<'a>(
+        &'a mut self,
+        data: &'a AppState,
+    ) -> Option<(&'a ViewState, &'a mut Viewport, f64)> {
+        let view = data.views.focused()?;
+        let line_height = self.font.as_ref()?.line_height;
+        Some((
+            view,
+            self.viewports.entry(view.id).or_default(),
+            line_height,
+        ))
+    }
+
+    fn scroll_by(&mut self, data: &AppState, delta: Vec2) -> bool {
+        let (view, viewport, line_height) = match self.focused_viewport(data) {
+            Some(focused) => focused,
+            None => return false,
+        };
+        let content = EditView::content_size(view, viewport, line_height);
+        let scrolled = viewport.scroll_by(delta, content);
+        EditView::sync_core(data, view, viewport, line_height);
+        scrolled
+    }
+
+    fn paint_scrollbars(ctx: &mut PaintCtx, viewport: &Viewport, content: Size, color: &Color) {
+        let size = viewport.size;
+        if content.height > size.height {
+            let height = (size.height * size.height / content.height).max(SCROLLBAR_MIN);
+            let y0 = viewport.offset.y / (content.height - size.height) * (size.height - height);
+            let x0 = size.width - SCROLLBAR_WIDTH - 2.0;
+            let bar = Rect::new(x0, y0, x0 + SCROLLBAR_WIDTH, y0 + height);
+            ctx.fill(bar.to_rounded_rect(SCROLLBAR_WIDTH / 2.0), color);
+        }
+        if content.width > size.width {
+            let width = (size.width * size.width / content.width).max(SCROLLBAR_MIN);
+            let x0 = viewport.offset.x / (content.width - size.width) * (size.width - width);
+            let y0 = size.height - SCROLLBAR_WIDTH - 2.0;
+            let bar = Rect::new(x0, y0, x0 + width, y0 + SCROLLBAR_WIDTH);
+            ctx.fill(bar.to_rounded_rect(SCROLLBAR_WIDTH / 2.0), color);
+        }
+    }
+}
+
+const LEFT_PAD: f64 = 6.0;
+const SCROLLBAR_WIDTH: f64 = 6.0;
+const SCROLLBAR_MIN: f64 = 20.0;
+const CARET_WIDTH: f64 = 2.0;
+/// The style ids xi-core reserves for selections and find matches.
+const SELECTION_STYLE: u64 = 0;
+const FIND_STYLE: u64 = 1;
+const CARET_BLINK: Duration = Duration::from_millis(530);
+
+impl Widget<AppState> for EditView {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut AppState, _env: &Env) {
+        match event {
+            Event::Timer(token) if *token == self.blink_timer => {
+                self.caret_on = !self.caret_on;
+                self.blink_timer = ctx.request_timer(CARET_BLINK);
+                ctx.request_paint();
+            }
+            Event::Wheel(wheel) => {
+                let mut delta = wheel.wheel_delta;
+                if wheel.mods.shift() && delta.x == 0.0 {
+                    delta = Vec2::new(delta.y, 0.0);
+                }
+                if self.scroll_by(data, delta) {
+                    ctx.request_paint();
+                }
+                ctx.set_handled();
+            }
+            Event::MouseDown(mouse) => {
+                ctx.request_focus();
+                if mouse.pos.x < self.gutter_width {
+                    self.toggle_bookmark(ctx.text(), data, mouse.pos);
+                } else if mouse.button.is_left() {
+                    ctx.set_active(true);
+                    if let Some((line, col)) = self.hit_test(ctx.text(), data, mouse.pos) {
+                        data.edit_focused(mouse::click_command(line, col, mouse.mods, mouse.count));
+                    }
+                }
+            }
+            Event::MouseMove(mouse) => {
+                if mouse.pos.x < self.gutter_width && !ctx.is_active() {
+                    ctx.set_cursor(&Cursor::Arrow);
+                } else {
+                    ctx.set_cursor(&Cursor::IBeam);
+                }
+                if ctx.is_active() {
+                    if let Some((line, col)) = self.hit_test(ctx.text(), data, mouse.pos) {
+                        data.edit_focused(mouse::drag_command(line, col, mouse.mods));
+                    }
+                }
+            }
+            Event::MouseUp(_) => {
+                if ctx.is_active() {
+                    ctx.set_active(false);
+                    ctx.request_paint();
+                }
+            }
+            Event::KeyDown(key) => {
+                if let Some(cmd) = keymap::edit_command(key) {
+                    data.edit_focused(cmd);
+                    ctx.set_handled();
+                }
+            }
+            Event::Command(cmd) if ctx.has_focus() && cmd.is(commands::COPY) => {
+                data.copy_focused(false);
+                ctx.set_handled();
+            }
+            Event::Command(cmd) if ctx.has_focus() && cmd.is(commands::CUT) => {
+                data.copy_focused(true);
+                ctx.set_handled();
+            }
+            Event::Paste(clipboard) => {
+                if let Some(chars) = clipboard.get_string() {
+                    data.edit_focused(EditCommand::Paste { chars });
+                    ctx.set_handled();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    #[rustfmt::skip]
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &AppState, _env: &Env) {
+        match event {
+            LifeCycle::WidgetAdded => ctx.register_for_focus(),
+            LifeCycle::Size(size) => self.send_size(data, *size),
+            LifeCycle::FocusChanged(true) => {
+                self.restart_blink(|duration| ctx.request_timer(duration));
+                ctx.request_paint();
+            }
+            LifeCycle::FocusChanged(false) => {
+                self.blink_timer = TimerToken::INVALID;
+                ctx.request_paint();
+            }
+            _ => {}
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &AppState, data: &AppState, _env: &Env) {
+        if old_data.theme_name != data.theme_name {
+            ctx.request_paint();
+        }
+
+        // todo: remove out
+        if old_data.current_file != data.current_file {
+            ctx.request_paint();
+        }
+
+        if !old_data.views.same_focused(&data.views) {
+            ctx.request_paint();
+        }
+
+        let old_view = old_data.views.focused();
+        let view = data.views.focused();
+        let extent = |view: Option<&ViewState>| {
+            view.map(|view| (view.id, view.line_cache.height(), view.config.clone()))
+        };
+        if extent(old_view) != extent(view)
+            || old_data.params.line_numbers != data.params.line_numbers
+        {
+            ctx.request_layout();
+        }
+        if let (Some(old_view), Some(view)) = (old_view, view) {
+            if old_view.id == view.id && old_view.cursor != view.cursor {
+                self.reveal_cursor = true;
+                ctx.request_layout();
+            }
+            if old_view.id == view.id && old_view.line_cache.revision != view.line_cache.revision {
+                if ctx.has_focus() {
+                    self.restart_blink(|duration| ctx.request_timer(duration));
+                }
+                if let Some((view, viewport, line_height)) = self.focused_viewport(data) {
+                    EditView::sync_core(data, view, viewport, line_height);
+                }
+            }
+        }
+
+        let wrap = |data: &AppState| {
+            data.views
+                .focused()
+                .map(|view| (view.id, view.config.word_wrap, view.config.wrap_width))
+        };
+        if wrap(old_data) != wrap(data) {
+            if let (Some(old), Some(new)) = (wrap(old_data), wrap(data)) {
+                // rewrapped lines, the widest one is measured again
+                if old.0 == new.0 {
+                    if let Some(viewport) = self.viewports.get_mut(&new.0) {
+                        viewport.content_width = 0.0;
+                    }
+                }
+            }
+            self.send_size(data, ctx.size());
+        }
+    }
+
+    fn layout(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &AppState,
+        _env: &Env,
+    ) -> Size {
+        let max_width = bc.max().width;
+        let max_height = bc.max().height;
+        let size = bc.constrain(Size::new(max_width, max_height));
+
+        let view = match data.views.focused() {
+            Some(view) => view,
+            None => return size,
+        };
+
+        let font = self.font(ctx.text(), view);
+        let line_height = font.line_height;
+        let gutter_width = gutter::width(
+            data.params.line_numbers,
+            view.line_cache.height(),
+            font.digit_width,
+        );
+        let foreground = EditView::foreground(data);
+        let visible = self
+            .viewports
+            .get(&view.id)
+            .map_or((0, 0), |viewport| viewport.visible_lines(line_height));
+        self.layouts
+            .sync(&font, &foreground, &data.styles, view, visible);
+        let cursor = if self.reveal_cursor {
+            Some(self.cursor_rect(ctx.text(), &font, view, &data.styles, &foreground))
+        } else {
+            None
+        };
+        self.reveal_cursor = false;
+        if gutter_width != self.gutter_width {
+            self.gutter_width = gutter_width;
+            self.send_size(data, size);
+        }
+
+        let viewport = self.viewports.entry(view.id).or_default();
+        viewport.size = Size::new((size.width - gutter_width).max(0.0), size.height);
+        let content = EditView::content_size(view, viewport, line_height);
+        viewport.clamp(content);
+        if let Some(cursor) = cursor {
+            viewport.reveal(cursor, content);
+        }
+        EditView::sync_core(data, view, viewport, line_height);
+
+        size
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &AppState, _env: &Env) {
+        let background = match &data.theme.background {
+            None => Color::WHITE,
+            Some(color) => theme::from_xi_color(color),
+        };
+
+        let foreground = EditView::foreground(data);
+
+        let size = ctx.size();
+        let rect = size.to_rect();
+        ctx.fill(rect, &background);
+
+        let view = match data.views.focused() {
+            Some(view) => view,
+            None => return,
+        };
+
+        let mut viewport = self.viewports.get(&view.id).cloned().unwrap_or_default();
+        let gutter_width = self.gutter_width;
+        let show_carets = ctx.has_focus() && self.caret_on;
+        let font = self.font(ctx.text(), view);
+        let line_height = font.line_height;
+        let (first, last) = viewport.visible_lines(line_height);
+
+        let last = last.min(view.line_cache.height());
+        self.layouts
+            .sync(&font, &foreground, &data.styles, view, (first, last));
+        let mut missing = vec![];
+        for n in first..last {
+            match view.line_cache.get_line(n) {
+                Some(line) => {
+                    self.layouts
+                        .layout(ctx.text(), &font, line, &data.styles, &foreground);
+                }
+                None => missing.push(n),
+            }
+        }
+        let cache = &self.layouts;
+        let layouts: Vec<_> = (first..last)
+            .filter_map(|n| {
+                let line = view.line_cache.get_line(n)?;
+                Some((n, line, cache.get(line)?))
+            })
+            .collect();
+
+        let line_highlight = data.theme.line_highlight.as_ref().map(theme::from_xi_color);
+        let caret = match &data.theme.caret {
+            Some(color) => theme::from_xi_color(color),
+            None => foreground.clone(),
+        };
+        let annotated = view.line_cache.annotations.is_some();
+        let annotation_colors: Vec<(AnnotationType, Color)> = vec![
+            (
+                AnnotationType::Selection,
+                &data.theme.selection,
+                SELECTION_STYLE,
+            ),
+            (AnnotationType::Find, &data.theme.find_highlight, FIND_STYLE),
+        ]
+        .into_iter()
+        .filter_map(|(annotation_type, theme_color, style_id)| {
+            let color = match theme_color {
+                Some(color) => theme::from_xi_color(color),
+                None => {
+                    let style = data.styles.get(&(style_id as usize))?;
+                    theme::color_from_u32(style.bg_color?)
+                }
+            };
+            Some((annotation_type, color))
+        })
+        .collect();
+        let placeholder = foreground.clone().with_alpha(0.08);
+        let text_width = viewport.size.width;
+        let placeholder_width = (text_width * 0.4).max(LEFT_PAD);
+        ctx.with_save(|ctx| {
+            ctx.clip(Rect::new(gutter_width, 0.0, size.width, size.height));
+            ctx.transform(Affine::translate(
+                Vec2::new(gutter_width, 0.0) - viewport.offset,
+            ));
+            for (n, line, layout) in layouts.iter() {
+                let y = *n as f64 * line_height;
+
+                if let (Some(color), false) = (&line_highlight, line.cursor.is_empty()) {
+                    let x0 = viewport.offset.x;
+                    ctx.fill(Rect::new(x0, y, x0 + text_width, y + line_height), color);
+                }
+
+                // selections and find matches, from their annotations or
+                // else styles 0 and 1, and any other style with a background
+                let mut backgrounds = vec![];
+                for (start, end, style_id) in style_spans(line) {
+                    if annotated && style_id <= FIND_STYLE {
+                        continue;
+                    }
+                    let style = data.styles.get(&(style_id as usize));
+                    if let Some(bg_color) = style.and_then(|style| style.bg_color) {
+                        backgrounds.push((start, end, theme::color_from_u32(bg_color)));
+                    }
+                }
+                if annotated {
+                    for (annotation_type, color) in annotation_colors.iter() {
+                        let ranges = view.line_cache.annotation_ranges(*n, annotation_type);
+                        for (start, end) in ranges {
+                            backgrounds.push((start, end, color.clone()));
+                        }
+                    }
+                }
+                for (start, end, color) in backgrounds.iter() {
+                    for range in layout.range_rects(*start, *end) {
+                        let x0 = LEFT_PAD + range.x0;
+                        let x1 = LEFT_PAD + range.x1;
+                        ctx.fill(Rect::new(x0, y, x1, y + line_height), color);
+                    }
+                }
+
+                ctx.draw_text(&layout.layout, (LEFT_PAD, y));
+
+                if show_carets {
+                    for offset in line.cursor.iter() {
+                        let x = LEFT_PAD + layout.caret_x(*offset as usize);
+                        let bar = Rect::new(x, y, x + CARET_WIDTH, y + line_height);
+                        ctx.fill(bar, &caret);
+                    }
+                }
+            }
+            // lines xi-core hasn't sent yet, `request_lines` is on its way
+            for n in missing.iter() {
+                let y = *n as f64 * line_height;
+                let bar = Rect::new(
+                    LEFT_PAD,
+                    y + 3.0,
+                    LEFT_PAD + placeholder_width,
+                    y + line_height - 3.0,
+                );
+                ctx.fill(bar.to_rounded_rect(3.0), &placeholder);
+            }
+        });
+
+        let colors = GutterColors {
+            background: match &data.theme.gutter {
+                Some(color) => theme::from_xi_color(color),
+                None => background.clone(),
+            },
+            foreground: match &data.theme.gutter_foreground {
+                Some(color) => theme::from_xi_color(color),
+                None => foreground.clone().with_alpha(0.5),
+            },
+            current: foreground.clone(),
+        };
+        gutter::paint(
+            ctx,
+            &font,
+            view,
+            data.params.line_numbers,
+            (first, last),
+            viewport.offset.y,
+            gutter_width,
+            &colors,
+        );
+
+        let widest = layouts
+            .iter()
+            .map(|(_, _, layout)| layout.layout.size().width)
+            .fold(viewport.content_width, f64::max);
+        viewport.content_width = widest;
+        let content = EditView::content_size(view, &viewport, line_height);
+        ctx.with_save(|ctx| {
+            ctx.transform(Affine::translate((gutter_width, 0.0)));
+            EditView::paint_scrollbars(ctx, &viewport, content, &foreground.with_alpha(0.3));
+        });
+        self.viewports.insert(view.id, viewport);
+    }
+}
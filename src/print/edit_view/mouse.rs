@@ -0,0 +1,81 @@
+use druid::Modifiers;
+
+use crate::rpc::structs::FLAG_SELECT;
+use crate::{EditCommand, GestureType};
+
+/// The edit a mouse down at `line`, `col` stands for: a plain `click`,
+/// shift-click extending the selection, double and triple clicks selecting
+/// words and lines, and alt adding cursors instead of replacing them.
+pub fn click_command(line: u64, col: u64, mods: Modifiers, count: u8) -> EditCommand {
+    let gesture = |ty| EditCommand::Gesture { line, col, ty };
+    if mods.shift() {
+        return EditCommand::Click(line, col, FLAG_SELECT, count as u64);
+    }
+    match (mods.alt(), count) {
+        (true, 0..=1) => gesture(GestureType::ToggleSel),
+        (true, 2) => gesture(GestureType::MultiWordSelect),
+        (true, _) => gesture(GestureType::MultiLineSelect),
+        (false, 2) => gesture(GestureType::WordSelect),
+        (false, 3..=u8::MAX) => gesture(GestureType::LineSelect),
+        (false, _) => EditCommand::Click(line, col, 0, 1),
+    }
+}
+
+/// The edit for moving the mouse to `line`, `col` with the button held.
+pub fn drag_command(line: u64, col: u64, mods: Modifiers) -> EditCommand {
+    let flags = if mods.shift() { FLAG_SELECT } else { 0 };
+    EditCommand::Drag(line, col, flags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clicks_select_points_words_and_lines() {
+        let none = Modifiers::empty();
+        assert_eq!(EditCommand::Click(2, 5, 0, 1), click_command(2, 5, none, 1));
+        assert_eq!(
+            EditCommand::Gesture {
+                line: 2,
+                col: 5,
+                ty: GestureType::WordSelect
+            },
+            click_command(2, 5, none, 2)
+        );
+        assert_eq!(
+            EditCommand::Gesture {
+                line: 2,
+                col: 5,
+                ty: GestureType::LineSelect
+            },
+            click_command(2, 5, none, 3)
+        );
+        assert_eq!(
+            EditCommand::Click(2, 5, FLAG_SELECT, 1),
+            click_command(2, 5, Modifiers::SHIFT, 1)
+        );
+    }
+
+    #[test]
+    fn alt_adds_cursors() {
+        let alt = Modifiers::ALT;
+        assert_eq!(
+            EditCommand::Gesture {
+                line: 0,
+                col: 1,
+                ty: GestureType::ToggleSel
+            },
+            click_command(0, 1, alt, 1)
+        );
+        assert_eq!(
+            EditCommand::Gesture {
+                line: 0,
+                col: 1,
+                ty: GestureType::MultiWordSelect
+            },
+            click_command(0, 1, alt, 2)
+        );
+        assert_eq!(EditCommand::Drag(4, 0, 0), drag_command(4, 0, alt));
+    }
+}
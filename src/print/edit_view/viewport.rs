@@ -0,0 +1,154 @@
+use druid::{Rect, Size, Vec2};
+
+/// How far one view is scrolled, in pixels from the top left of its text.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Viewport {
+    pub offset: Vec2,
+    /// The size of the editor widget.
+    pub size: Size,
+    /// The widest line painted so far; lines are measured as they show up.
+    pub content_width: f64,
+    /// What we last told xi-core with the `scroll` edit.
+    pub sent_range: Option<(u64, u64)>,
+    /// Ranges asked for with `request_lines` since `requested_revision`.
+    requested: Vec<(u64, u64)>,
+    requested_revision: u64,
+}
+
+impl Viewport {
+    /// Keeps the offset within `content`.
+    pub fn clamp(&mut self, content: Size) {
+        let max_x = (content.width - self.size.width).max(0.0);
+        let max_y = (content.height - self.size.height).max(0.0);
+        self.offset.x = self.offset.x.max(0.0).min(max_x);
+        self.offset.y = self.offset.y.max(0.0).min(max_y);
+    }
+
+    /// Returns whether the offset changed.
+    pub fn scroll_by(&mut self, delta: Vec2, content: Size) -> bool {
+        let old = self.offset;
+        self.offset += delta;
+        self.clamp(content);
+        old != self.offset
+    }
+
+    /// Scrolls as little as needed to show `rect`, given in content
+    /// coordinates. Returns whether the offset changed.
+    pub fn reveal(&mut self, rect: Rect, content: Size) -> bool {
+        let old = self.offset;
+        if rect.y0 < self.offset.y {
+            self.offset.y = rect.y0;
+        } else if rect.y1 > self.offset.y + self.size.height {
+            self.offset.y = rect.y1 - self.size.height;
+        }
+        if rect.x0 < self.offset.x {
+            self.offset.x = rect.x0;
+        } else if rect.x1 > self.offset.x + self.size.width {
+            self.offset.x = rect.x1 - self.size.width;
+        }
+        self.clamp(content);
+        old != self.offset
+    }
+
+    /// The first (inclusive) and last (exclusive) line that show, even partly.
+    pub fn visible_lines(&self, line_height: f64) -> (u64, u64) {
+        let first = (self.offset.y / line_height).floor().max(0.0);
+        let last = ((self.offset.y + self.size.height) / line_height).ceil();
+        (first as u64, last.max(first) as u64)
+    }
+
+    /// The visible lines, when they differ from the range xi-core knows about.
+    pub fn take_changed_range(&mut self, line_height: f64) -> Option<(u64, u64)> {
+        let range = self.visible_lines(line_height);
+        if self.sent_range == Some(range) {
+            return None;
+        }
+        self.sent_range = Some(range);
+        Some(range)
+    }
+
+    /// Filters out the `missing` ranges already requested. Once the line cache
+    /// moved on to another `revision`, what is still missing is asked for again.
+    pub fn take_unrequested(&mut self, missing: Vec<(u64, u64)>, revision: u64) -> Vec<(u64, u64)> {
+        if revision != self.requested_revision {
+            self.requested.clear();
+            self.requested_revision = revision;
+        }
+        let requested = &self.requested;
+        let unrequested: Vec<(u64, u64)> = missing
+            .into_iter()
+            .filter(|(first, last)| {
+                !requested
+                    .iter()
+                    .any(|(start, end)| start <= first && last <= end)
+            })
+            .collect();
+        self.requested.extend(unrequested.iter().copied());
+        unrequested
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn viewport() -> Viewport {
+        Viewport {
+            size: Size::new(100.0, 50.0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn scroll_stays_within_content() {
+        let mut viewport = viewport();
+        let content = Size::new(300.0, 200.0);
+
+        assert!(!viewport.scroll_by(Vec2::new(-10.0, -10.0), content));
+        assert!(viewport.scroll_by(Vec2::new(20.0, 500.0), content));
+        assert_eq!(Vec2::new(20.0, 150.0), viewport.offset);
+
+        // content smaller than the widget doesn't scroll at all
+        assert!(viewport.scroll_by(Vec2::ZERO, Size::new(80.0, 40.0)));
+        assert_eq!(Vec2::ZERO, viewport.offset);
+    }
+
+    #[test]
+    fn visible_lines_include_partial_ones() {
+        let mut viewport = viewport();
+        assert_eq!((0, 5), viewport.visible_lines(10.0));
+
+        viewport.offset.y = 25.0;
+        assert_eq!((2, 8), viewport.visible_lines(10.0));
+
+        assert_eq!(Some((2, 8)), viewport.take_changed_range(10.0));
+        assert_eq!(None, viewport.take_changed_range(10.0));
+    }
+
+    #[test]
+    fn missing_lines_are_requested_once_per_revision() {
+        let mut viewport = viewport();
+        assert_eq!(vec![(0, 10)], viewport.take_unrequested(vec![(0, 10)], 1));
+        assert!(viewport.take_unrequested(vec![(0, 10)], 1).is_empty());
+        assert!(viewport.take_unrequested(vec![(2, 5)], 1).is_empty());
+        assert_eq!(
+            vec![(20, 30)],
+            viewport.take_unrequested(vec![(2, 5), (20, 30)], 1)
+        );
+
+        assert_eq!(vec![(2, 5)], viewport.take_unrequested(vec![(2, 5)], 2));
+    }
+
+    #[test]
+    fn reveal_scrolls_the_least() {
+        let mut viewport = viewport();
+        let content = Size::new(300.0, 200.0);
+
+        assert!(!viewport.reveal(Rect::new(0.0, 10.0, 10.0, 20.0), content));
+        assert!(viewport.reveal(Rect::new(0.0, 90.0, 10.0, 100.0), content));
+        assert_eq!(Vec2::new(0.0, 50.0), viewport.offset);
+
+        assert!(viewport.reveal(Rect::new(150.0, 40.0, 160.0, 50.0), content));
+        assert_eq!(Vec2::new(60.0, 40.0), viewport.offset);
+    }
+}
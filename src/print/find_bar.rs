@@ -0,0 +1,137 @@
+use druid::widget::{Checkbox, Controller, Either, Flex, Label, SizedBox, TextBox};
+use druid::{Env, Event, EventCtx, KbKey, LocalizedString, Selector, Widget, WidgetExt};
+
+use crate::app_command::print_command;
+use crate::app_state::AppState;
+use crate::components::icon_button::IconButton;
+use crate::model::find::{match_count, FindState};
+use crate::support::line;
+use crate::theme;
+
+/// The find/replace bar shown above the editor while `FindState::visible`.
+pub fn find_bar() -> impl Widget<AppState> {
+    Either::new(
+        |data: &AppState, _env| data.find.visible,
+        bar(),
+        SizedBox::empty(),
+    )
+}
+
+fn bar() -> impl Widget<AppState> {
+    let find_row = Flex::row()
+        .with_flex_child(
+            TextBox::new()
+                .with_placeholder("Find")
+                .expand_width()
+                .lens(FindState::query),
+            1.0,
+        )
+        .with_default_spacer()
+        .with_child(toggle("find-case-sensitive", "Match Case").lens(FindState::case_sensitive))
+        .with_child(toggle("find-regex", "Regex").lens(FindState::regex))
+        .with_child(toggle("find-whole-words", "Words").lens(FindState::whole_words))
+        .lens(AppState::find);
+
+    let replace_row = TextBox::new()
+        .with_placeholder("Replace")
+        .expand_width()
+        .lens(FindState::replacement)
+        .lens(AppState::find);
+
+    let counter = Label::dynamic(|data: &AppState, _env| {
+        let status = data
+            .views
+            .focused()
+            .and_then(|view| view.find_status.as_ref());
+        match status.map(match_count) {
+            None => String::new(),
+            Some(0) => "No results".to_string(),
+            Some(1) => "1 match".to_string(),
+            Some(n) => format!("{} matches", n),
+        }
+    })
+    .with_text_color(theme::BASIC_TEXT_COLOR);
+
+    Flex::column()
+        .with_child(
+            Flex::row()
+                .with_flex_child(find_row, 1.0)
+                .with_default_spacer()
+                .with_child(counter)
+                .with_default_spacer()
+                .with_child(button(
+                    "find-previous",
+                    "Previous",
+                    print_command::FIND_PREVIOUS,
+                ))
+                .with_child(button("find-next", "Next", print_command::FIND_NEXT))
+                .with_child(button("find-close", "Close", print_command::HIDE_FIND)),
+        )
+        .with_spacer(4.0)
+        .with_child(
+            Flex::row()
+                .with_flex_child(replace_row, 1.0)
+                .with_default_spacer()
+                .with_child(button(
+                    "replace-next",
+                    "Replace",
+                    print_command::REPLACE_NEXT,
+                ))
+                .with_child(button(
+                    "replace-all",
+                    "Replace All",
+                    print_command::REPLACE_ALL,
+                )),
+        )
+        .padding(5.0)
+        .expand_width()
+        .background(line::hline())
+        .controller(FindController)
+}
+
+fn toggle(key: &'static str, placeholder: &'static str) -> impl Widget<bool> {
+    Checkbox::new(LocalizedString::new(key).with_placeholder(placeholder)).padding((4.0, 0.0))
+}
+
+fn button(key: &'static str, placeholder: &'static str, cmd: Selector) -> impl Widget<AppState> {
+    let label = Label::new(LocalizedString::new(key).with_placeholder(placeholder))
+        .with_text_color(theme::BASIC_TEXT_COLOR);
+    IconButton::from_label(label).on_click(move |ctx, _data, _env| ctx.submit_command(cmd))
+}
+
+/// Searches again whenever the query or a toggle changes; Enter jumps to the
+/// next match, Shift+Enter to the previous one and Escape closes the bar.
+struct FindController;
+
+impl<W: Widget<AppState>> Controller<AppState, W> for FindController {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut AppState,
+        env: &Env,
+    ) {
+        if let Event::KeyDown(key) = event {
+            match key.key {
+                KbKey::Enter => {
+                    data.find_next(key.mods.shift());
+                    ctx.set_handled();
+                    return;
+                }
+                KbKey::Escape => {
+                    data.hide_find();
+                    ctx.set_handled();
+                    return;
+                }
+                _ => {}
+            }
+        }
+
+        let old = data.find.clone();
+        child.event(ctx, event, data, env);
+        if data.find.query_changed(&old) {
+            data.find();
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use druid::widget::{Controller, Either, Flex, Label, SizedBox, TextBox};
+use druid::{Env, Event, EventCtx, KbKey, LocalizedString, Widget, WidgetExt};
+
+use crate::app_command::print_command;
+use crate::app_state::AppState;
+use crate::components::icon_button::IconButton;
+use crate::model::goto_line::GotoLineState;
+use crate::support::line;
+use crate::theme;
+
+/// The bar asking for a line to go to, shown above the editor while
+/// `GotoLineState::visible`.
+pub fn goto_bar() -> impl Widget<AppState> {
+    Either::new(
+        |data: &AppState, _env| data.goto_line.visible,
+        bar(),
+        SizedBox::empty(),
+    )
+}
+
+fn bar() -> impl Widget<AppState> {
+    let query = TextBox::new()
+        .with_placeholder("Line number")
+        .expand_width()
+        .lens(GotoLineState::query)
+        .lens(AppState::goto_line);
+
+    let go = Label::new(LocalizedString::new("goto-line-go").with_placeholder("Go"))
+        .with_text_color(theme::BASIC_TEXT_COLOR);
+    let close = Label::new(LocalizedString::new("goto-line-close").with_placeholder("Close"))
+        .with_text_color(theme::BASIC_TEXT_COLOR);
+
+    Flex::row()
+        .with_flex_child(query, 1.0)
+        .with_default_spacer()
+        .with_child(
+            IconButton::from_label(go)
+                .on_click(|ctx, data: &mut AppState, _env| GotoController::submit(ctx, data)),
+        )
+        .with_child(
+            IconButton::from_label(close)
+                .on_click(|ctx, _data, _env| ctx.submit_command(print_command::HIDE_GOTO_LINE)),
+        )
+        .padding(5.0)
+        .expand_width()
+        .background(line::hline())
+        .controller(GotoController)
+}
+
+/// Enter goes to the line typed in, Escape closes the bar.
+struct GotoController;
+
+impl GotoController {
+    fn submit(ctx: &mut EventCtx, data: &mut AppState) {
+        if let Some(line_num) = data.goto_line.line_num() {
+            ctx.submit_command(print_command::GOTO_LINE.with(line_num));
+            ctx.submit_command(print_command::HIDE_GOTO_LINE);
+        }
+    }
+}
+
+impl<W: Widget<AppState>> Controller<AppState, W> for GotoController {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut AppState,
+        env: &Env,
+    ) {
+        if let Event::KeyDown(key) = event {
+            match key.key {
+                KbKey::Enter => {
+                    GotoController::submit(ctx, data);
+                    ctx.set_handled();
+                    return;
+                }
+                KbKey::Escape => {
+                    ctx.submit_command(print_command::HIDE_GOTO_LINE);
+                    ctx.set_handled();
+                    return;
+                }
+                _ => {}
+            }
+        }
+        child.event(ctx, event, data, env);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::app_command::print_command;
 use crate::app_state::AppState;
+use crate::model::gutter::LineNumbers;
 use druid::{
     commands, platform_menus, Data, Env, FileDialogOptions, LocalizedString, Menu, MenuItem,
     SysMods, WindowId,
@@ -13,13 +14,54 @@ pub fn make_menu(_: Option<WindowId>, state: &AppState, _: &Env) -> Menu<AppStat
         menu = menu.entry(platform_menus::mac::application::default());
     }
 
-    menu.entry(file_menu()).entry(view_menu(state)).rebuild_on(
-        |old_data: &AppState, data: &AppState, _env| data.themes.len() != old_data.themes.len(),
-    )
+    menu.entry(file_menu())
+        .entry(edit_menu())
+        .entry(view_menu(state))
+        .rebuild_on(|old_data: &AppState, data: &AppState, _env| {
+            data.themes.len() != old_data.themes.len()
+                || data.languages.len() != old_data.languages.len()
+        })
 }
 
 fn view_menu(state: &AppState) -> Menu<AppState> {
-    Menu::new(LocalizedString::new("common-menu-view-menu")).entry(themes_menu(state))
+    Menu::new(LocalizedString::new("common-menu-view-menu"))
+        .entry(themes_menu(state))
+        .entry(languages_menu(state))
+        .entry(
+            MenuItem::new(LocalizedString::new("common-menu-plugins"))
+                .command(print_command::TOGGLE_PLUGINS),
+        )
+        .entry(
+            MenuItem::new(LocalizedString::new("common-menu-event-log"))
+                .command(print_command::TOGGLE_EVENT_LOG),
+        )
+        .entry(line_numbers_menu())
+        .entry(
+            MenuItem::new(LocalizedString::new("common-menu-word-wrap"))
+                .command(print_command::TOGGLE_WORD_WRAP)
+                .selected_if(|data: &AppState, _env| {
+                    data.views
+                        .focused()
+                        .map_or(false, |view| view.config.wraps())
+                }),
+        )
+}
+
+fn line_numbers_menu() -> Menu<AppState> {
+    let modes = [
+        ("common-menu-line-numbers-absolute", LineNumbers::Absolute),
+        ("common-menu-line-numbers-relative", LineNumbers::Relative),
+        ("common-menu-line-numbers-hidden", LineNumbers::Hidden),
+    ];
+    let mut menu = Menu::new(LocalizedString::new("common-menu-line-numbers"));
+    for (key, mode) in modes.iter().copied() {
+        menu = menu.entry(
+            MenuItem::new(LocalizedString::new(key))
+                .command(print_command::SET_LINE_NUMBERS.with(mode))
+                .selected_if(move |data: &AppState, _env| data.params.line_numbers == mode),
+        );
+    }
+    menu
 }
 
 fn file_menu<T: Data>() -> Menu<T> {
@@ -36,6 +78,54 @@ fn file_menu<T: Data>() -> Menu<T> {
         .entry(platform_menus::mac::file::close())
 }
 
+fn edit_menu<T: Data>() -> Menu<T> {
+    Menu::new(LocalizedString::new("common-menu-edit-menu"))
+        .entry(platform_menus::common::cut())
+        .entry(platform_menus::common::copy())
+        .entry(platform_menus::common::paste())
+        .separator()
+        .entry(
+            MenuItem::new(LocalizedString::new("common-menu-find"))
+                .command(print_command::SHOW_FIND)
+                .hotkey(SysMods::Cmd, "f"),
+        )
+        .entry(
+            MenuItem::new(LocalizedString::new("common-menu-find-next"))
+                .command(print_command::FIND_NEXT)
+                .hotkey(SysMods::Cmd, "g"),
+        )
+        .entry(
+            MenuItem::new(LocalizedString::new("common-menu-find-previous"))
+                .command(print_command::FIND_PREVIOUS)
+                .hotkey(SysMods::CmdShift, "G"),
+        )
+        .separator()
+        .entry(
+            MenuItem::new(LocalizedString::new("common-menu-goto-line"))
+                .command(print_command::SHOW_GOTO_LINE)
+                .hotkey(SysMods::Cmd, "l"),
+        )
+}
+
+fn languages_menu(state: &AppState) -> Menu<AppState> {
+    let mut languages_menu: Menu<AppState> =
+        Menu::new(LocalizedString::new("common-menu-languages-menu"));
+    for language in &state.languages {
+        let string = language.clone();
+        let current = language.clone();
+        languages_menu = languages_menu.entry(
+            MenuItem::new(string.clone())
+                .command(print_command::SET_LANGUAGE.with(string))
+                .selected_if(move |data: &AppState, _env| {
+                    let view = data.views.focused();
+                    view.and_then(|view| view.language.as_ref()) == Some(&current)
+                }),
+        );
+    }
+
+    languages_menu
+}
+
 fn themes_menu(state: &AppState) -> Menu<AppState> {
     let mut themes_menu: Menu<AppState> =
         Menu::new(LocalizedString::new("common-menu-themes-menu"));
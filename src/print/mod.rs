@@ -1,3 +1,5 @@
+pub use tool_window::event_log_tool_window::EventLogToolWindow;
+pub use tool_window::plugin_tool_window::PluginToolWindow;
 pub use tool_window::project_tool_window::ProjectToolWindow;
 pub use tool_window::ToolWindow;
 
@@ -10,6 +12,8 @@ pub mod tool_bar;
 pub mod bar_support;
 pub mod color;
 pub mod edit_view;
+pub mod find_bar;
+pub mod goto_bar;
 pub mod menu;
 pub mod tabs;
-pub mod text_edit_view;
+pub mod toasts;
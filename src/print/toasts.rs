@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use druid::widget::prelude::*;
+use druid::widget::{Flex, Label, LineBreaking};
+use druid::{Color, Rect, TimerToken, WidgetExt, WidgetPod};
+
+use crate::app_state::AppState;
+use crate::components::icon_button::IconButton;
+use crate::model::notification::{Level, Notification};
+
+const TOAST_DURATION: Duration = Duration::from_secs(6);
+const TOAST_WIDTH: f64 = 320.0;
+const TOAST_MARGIN: f64 = 16.0;
+
+/// Shows the pending toasts of `AppState::notifications` over the bottom
+/// right corner of its child, each one until it's closed or times out.
+pub struct ToastHost {
+    child: WidgetPod<AppState, Box<dyn Widget<AppState>>>,
+    toasts: WidgetPod<AppState, Box<dyn Widget<AppState>>>,
+    timers: HashMap<TimerToken, u64>,
+}
+
+impl ToastHost {
+    pub fn new(child: impl Widget<AppState> + 'static) -> ToastHost {
+        ToastHost {
+            child: WidgetPod::new(child.boxed()),
+            toasts: WidgetPod::new(Flex::column().boxed()),
+            timers: HashMap::new(),
+        }
+    }
+
+    fn rebuild_toasts(&mut self, data: &AppState) {
+        let mut column = Flex::column();
+        for toast in data.notifications.toast_entries() {
+            column.add_child(ToastHost::toast(toast));
+            column.add_spacer(8.0);
+        }
+        self.toasts = WidgetPod::new(column.boxed());
+    }
+
+    fn toast(toast: &Notification) -> impl Widget<AppState> {
+        let id = toast.id;
+        let close = IconButton::from_label(Label::new("×").with_text_color(Color::WHITE))
+            .on_click(move |_ctx, data: &mut AppState, _env| data.notifications.dismiss(id));
+
+        Flex::row()
+            .with_flex_child(
+                Label::new(toast.message.clone())
+                    .with_line_break_mode(LineBreaking::WordWrap)
+                    .with_text_color(Color::WHITE)
+                    .expand_width(),
+                1.0,
+            )
+            .with_child(close)
+            .padding(8.0)
+            .background(ToastHost::color(toast.level))
+            .rounded(4.0)
+            .fix_width(TOAST_WIDTH)
+    }
+
+    fn color(level: Level) -> Color {
+        match level {
+            Level::Info => Color::rgb8(0x3c, 0x6e, 0xa8),
+            Level::Warning => Color::rgb8(0xb0, 0x7a, 0x10),
+            Level::Error => Color::rgb8(0xb0, 0x30, 0x30),
+        }
+    }
+
+    /// Starts a timeout for every toast that isn't already counting down.
+    fn schedule(&mut self, data: &AppState, mut request_timer: impl FnMut() -> TimerToken) {
+        for id in data.notifications.toasts.iter() {
+            if !self.timers.values().any(|scheduled| scheduled == id) {
+                self.timers.insert(request_timer(), *id);
+            }
+        }
+    }
+}
+
+impl Widget<AppState> for ToastHost {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut AppState, env: &Env) {
+        if let Event::Timer(token) = event {
+            if let Some(id) = self.timers.remove(token) {
+                data.notifications.dismiss(id);
+                ctx.set_handled();
+                return;
+            }
+        }
+
+        self.toasts.event(ctx, event, data, env);
+        if !ctx.is_handled() {
+            self.child.event(ctx, event, data, env);
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &AppState, env: &Env) {
+        if let LifeCycle::WidgetAdded = event {
+            // errors from loading the config come in before the window exists
+            self.schedule(data, || ctx.request_timer(TOAST_DURATION));
+            self.rebuild_toasts(data);
+        }
+        self.toasts.lifecycle(ctx, event, data, env);
+        self.child.lifecycle(ctx, event, data, env);
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &AppState, data: &AppState, env: &Env) {
+        if old_data.notifications.toasts != data.notifications.toasts {
+            self.timers
+                .retain(|_, id| data.notifications.toasts.contains(id));
+            self.schedule(data, || ctx.request_timer(TOAST_DURATION));
+            self.rebuild_toasts(data);
+            ctx.children_changed();
+        } else {
+            self.toasts.update(ctx, data, env);
+        }
+        self.child.update(ctx, data, env);
+    }
+
+    fn layout(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &AppState,
+        env: &Env,
+    ) -> Size {
+        let size = self.child.layout(ctx, bc, data, env);
+        self.child.set_layout_rect(ctx, data, env, size.to_rect());
+
+        let toasts_bc = BoxConstraints::new(Size::ZERO, size);
+        let toasts_size = self.toasts.layout(ctx, &toasts_bc, data, env);
+        let origin = (
+            (size.width - toasts_size.width - TOAST_MARGIN).max(0.0),
+            (size.height - toasts_size.height - TOAST_MARGIN).max(0.0),
+        );
+        let toasts_frame = Rect::from_origin_size(origin, toasts_size);
+        self.toasts.set_layout_rect(ctx, data, env, toasts_frame);
+        size
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &AppState, env: &Env) {
+        self.child.paint(ctx, data, env);
+        self.toasts.paint(ctx, data, env);
+    }
+}
@@ -0,0 +1,125 @@
+use druid::widget::{Flex, Label, LineBreaking, Scroll, SizedBox};
+use druid::{
+    BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx,
+    LocalizedString, PaintCtx, Size, UpdateCtx, Widget, WidgetExt,
+};
+
+use crate::app_state::AppState;
+use crate::components::icon_button::IconButton;
+use crate::model::notification::Notification;
+
+const EVENT_LOG_HEIGHT: f64 = 160.0;
+
+/// Everything that went through `AppState::notify` or came in as an xi-core
+/// alert, newest first.
+pub struct EventLogToolWindow {
+    inner: Box<dyn Widget<AppState>>,
+}
+
+impl EventLogToolWindow {
+    pub fn new() -> EventLogToolWindow {
+        EventLogToolWindow {
+            inner: SizedBox::empty().boxed(),
+        }
+    }
+
+    fn rebuild_inner(&mut self, data: &AppState) {
+        let clear = Label::new(LocalizedString::new("event-log-clear").with_placeholder("Clear"))
+            .with_text_color(crate::theme::BASIC_TEXT_COLOR)
+            .with_text_size(crate::theme::BASIC_TEXT_SIZE);
+        let header = Flex::row()
+            .with_child(
+                Label::new(LocalizedString::new("event-log-title").with_placeholder("Event Log"))
+                    .with_text_color(crate::theme::BASIC_TEXT_COLOR),
+            )
+            .with_flex_spacer(1.0)
+            .with_child(
+                IconButton::from_label(clear)
+                    .on_click(|_ctx, data: &mut AppState, _env| data.notifications.clear()),
+            );
+
+        let mut entries = Flex::column();
+        if data.notifications.entries.is_empty() {
+            entries.add_child(
+                Label::new(LocalizedString::new("event-log-empty").with_placeholder("No events"))
+                    .with_text_color(crate::theme::BASIC_TEXT_COLOR)
+                    .with_text_size(crate::theme::BASIC_TEXT_SIZE),
+            );
+        }
+        for entry in data.notifications.entries.iter().rev() {
+            entries.add_child(EventLogToolWindow::entry_row(entry));
+        }
+
+        let flex = Flex::column()
+            .with_child(header)
+            .with_flex_child(
+                Scroll::new(entries.expand_width())
+                    .vertical()
+                    .expand_height(),
+                1.0,
+            )
+            .padding(5.0)
+            .fix_height(EVENT_LOG_HEIGHT)
+            .expand_width()
+            .background(crate::theme::SIDEBAR_BACKGROUND);
+
+        if data.params.debug_layout {
+            self.inner = flex.debug_paint_layout().boxed()
+        } else {
+            self.inner = flex.boxed();
+        }
+    }
+
+    fn entry_row(entry: &Notification) -> impl Widget<AppState> {
+        let text = format!(
+            "{} {:<7} {}",
+            entry.time_of_day(),
+            entry.level.name(),
+            entry.message
+        );
+        Label::new(text)
+            .with_line_break_mode(LineBreaking::WordWrap)
+            .with_text_color(crate::theme::BASIC_TEXT_COLOR)
+            .with_text_size(crate::theme::BASIC_TEXT_SIZE)
+            .padding((0.0, 2.0))
+            .expand_width()
+    }
+}
+
+impl Widget<AppState> for EventLogToolWindow {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut AppState, env: &Env) {
+        self.inner.event(ctx, event, data, env)
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &AppState, env: &Env) {
+        if let LifeCycle::WidgetAdded = event {
+            self.rebuild_inner(data);
+        }
+        self.inner.lifecycle(ctx, event, data, env)
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &AppState, data: &AppState, env: &Env) {
+        if old_data.notifications.entries != data.notifications.entries
+            || !old_data.params.same(&data.params)
+        {
+            self.rebuild_inner(data);
+            ctx.children_changed();
+        } else {
+            self.inner.update(ctx, old_data, data, env);
+        }
+    }
+
+    fn layout(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &AppState,
+        env: &Env,
+    ) -> Size {
+        self.inner.layout(ctx, bc, data, env)
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &AppState, env: &Env) {
+        self.inner.paint(ctx, data, env);
+    }
+}
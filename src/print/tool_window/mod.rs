@@ -1,3 +1,5 @@
+pub mod event_log_tool_window;
+pub mod plugin_tool_window;
 pub mod project_tool_window;
 
 pub trait ToolWindow {}
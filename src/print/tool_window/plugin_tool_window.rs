@@ -0,0 +1,147 @@
+use druid::widget::{Flex, Label, LabelText, Scroll, SizedBox};
+use druid::{
+    BoxConstraints, Command, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx,
+    LocalizedString, PaintCtx, Size, UpdateCtx, Widget, WidgetExt,
+};
+
+use crate::app_command::print_command;
+use crate::app_state::AppState;
+use crate::components::icon_button::IconButton;
+use crate::model::plugin::{PluginState, Plugins};
+
+/// Lists the xi plugins of the focused view, with start/stop buttons and
+/// the commands each running plugin advertises.
+pub struct PluginToolWindow {
+    inner: Box<dyn Widget<AppState>>,
+}
+
+impl PluginToolWindow {
+    pub fn new() -> PluginToolWindow {
+        PluginToolWindow {
+            inner: SizedBox::empty().boxed(),
+        }
+    }
+
+    fn plugins(data: &AppState) -> Option<&Plugins> {
+        data.views.focused().map(|view| &view.plugins)
+    }
+
+    fn rebuild_inner(&mut self, data: &AppState) {
+        let mut flex = Flex::column().with_child(
+            Label::new(LocalizedString::new("plugins-title").with_placeholder("Plugins"))
+                .with_text_color(crate::theme::BASIC_TEXT_COLOR),
+        );
+
+        match PluginToolWindow::plugins(data) {
+            Some(plugins) if !plugins.0.is_empty() => {
+                for plugin in plugins.0.iter() {
+                    flex.add_child(PluginToolWindow::plugin_row(plugin));
+                }
+            }
+            _ => flex.add_child(
+                Label::new(LocalizedString::new("plugins-empty").with_placeholder("No plugins"))
+                    .with_text_color(crate::theme::BASIC_TEXT_COLOR)
+                    .with_text_size(crate::theme::BASIC_TEXT_SIZE),
+            ),
+        }
+
+        let flex = Scroll::new(flex.padding(5.0))
+            .vertical()
+            .background(crate::theme::SIDEBAR_BACKGROUND)
+            .expand_height();
+
+        if data.params.debug_layout {
+            self.inner = flex.debug_paint_layout().boxed()
+        } else {
+            self.inner = flex.boxed();
+        }
+    }
+
+    fn plugin_row(plugin: &PluginState) -> impl Widget<AppState> {
+        let name = plugin.name.clone();
+        let (state, toggle) = if plugin.running {
+            let cmd = print_command::STOP_PLUGIN.with(name.clone());
+            let stop = LocalizedString::new("plugins-stop").with_placeholder("Stop");
+            (
+                LocalizedString::new("plugins-running").with_placeholder("running"),
+                PluginToolWindow::button(stop, cmd),
+            )
+        } else {
+            let cmd = print_command::START_PLUGIN.with(name.clone());
+            let start = LocalizedString::new("plugins-start").with_placeholder("Start");
+            (
+                LocalizedString::new("plugins-stopped").with_placeholder("stopped"),
+                PluginToolWindow::button(start, cmd),
+            )
+        };
+
+        let mut column = Flex::column().with_child(
+            Flex::row()
+                .with_child(PluginToolWindow::label(name.clone()))
+                .with_default_spacer()
+                .with_child(PluginToolWindow::label(state))
+                .with_default_spacer()
+                .with_child(toggle),
+        );
+
+        // titles come from the plugin and are shown as it sent them
+        for plugin_cmd in plugin.cmds.iter() {
+            let title = plugin_cmd.title.clone();
+            let cmd = print_command::RUN_PLUGIN_CMD.with((name.clone(), plugin_cmd.clone()));
+            column.add_child(PluginToolWindow::button(title, cmd).padding((10.0, 0.0)));
+        }
+
+        column.padding((0.0, 4.0))
+    }
+
+    fn label(text: impl Into<LabelText<AppState>>) -> Label<AppState> {
+        Label::new(text)
+            .with_text_color(crate::theme::BASIC_TEXT_COLOR)
+            .with_text_size(crate::theme::BASIC_TEXT_SIZE)
+    }
+
+    fn button(text: impl Into<LabelText<AppState>>, cmd: Command) -> impl Widget<AppState> {
+        let label = PluginToolWindow::label(text);
+        IconButton::from_label(label).on_click(move |ctx, _data, _env| {
+            ctx.submit_command(cmd.clone());
+        })
+    }
+}
+
+impl Widget<AppState> for PluginToolWindow {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut AppState, env: &Env) {
+        self.inner.event(ctx, event, data, env)
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &AppState, env: &Env) {
+        if let LifeCycle::WidgetAdded = event {
+            self.rebuild_inner(data);
+        }
+        self.inner.lifecycle(ctx, event, data, env)
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &AppState, data: &AppState, env: &Env) {
+        let old = PluginToolWindow::plugins(old_data);
+        let new = PluginToolWindow::plugins(data);
+        if old != new || !old_data.params.same(&data.params) {
+            self.rebuild_inner(data);
+            ctx.children_changed();
+        } else {
+            self.inner.update(ctx, old_data, data, env);
+        }
+    }
+
+    fn layout(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &AppState,
+        env: &Env,
+    ) -> Size {
+        self.inner.layout(ctx, bc, data, env)
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &AppState, env: &Env) {
+        self.inner.paint(ctx, data, env);
+    }
+}
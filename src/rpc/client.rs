@@ -1,40 +1,75 @@
+use crate::rpc::errors::{ClientError, RequestError};
 use crate::rpc::message::{Message, Notification, Request, Response};
+use crate::rpc::recorder::{Direction, Recorder};
 use crate::rpc::structs::{
-    Alert, AvailableLanguages, AvailablePlugins, AvailableThemes, ConfigChanged, FindStatus,
-    LanguageChanged, MeasureWidth, PluginStarted, PluginStopped, ReplaceStatus, ScrollTo, Style,
-    ThemeChanged, Update, UpdateCmds,
+    Alert, AvailableLanguages, AvailablePlugins, AvailableThemes, ConfigChanged, EditCommand,
+    FindStatus, LanguageChanged, MeasureWidth, PluginRpc, PluginStarted, PluginStopped,
+    ReplaceStatus, ScrollTo, Style, ThemeChanged, Update, UpdateCmds, ViewId,
 };
-use crossbeam_channel::unbounded;
+use crate::rpc::transport::{InProcessTransport, Transport, XiReader, XiWriter};
+use crossbeam_channel::{unbounded, Sender};
 use druid::Data;
+use futures::channel::oneshot;
+use futures::executor::block_on;
 use log::*;
-use pipe::{pipe, PipeReader, PipeWriter};
-use serde_json::{self, from_value, json, to_vec, Value};
-use std::cell::Cell;
+use serde::de::DeserializeOwned;
+use serde_json::{self, from_value, json, to_value, Value};
 use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
-use std::io::{BufRead, Write};
-use std::sync::{Arc, Mutex};
+use std::future::Future;
+use std::io::{self, BufRead, Write};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 use std::{fmt, thread};
-use xi_core_lib::XiCore;
-use xi_rpc::RpcLoop;
 
-type XiSender = PipeWriter;
-type XiReceiver = PipeReader;
+type XiSender = Arc<Mutex<XiWriter>>;
+type PendingRequests = Arc<Mutex<HashMap<u64, PendingRequest>>>;
 
-pub trait Callback: Send {
-    fn call(self: Box<Self>, result: Result<Value, Value>);
+/// How long `Client::request` waits for xi-core before giving up.
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often expired and abandoned requests are swept.
+const REAP_INTERVAL: Duration = Duration::from_millis(500);
+
+struct PendingRequest {
+    deadline: Instant,
+    sender: oneshot::Sender<Result<Value, RequestError>>,
+}
+
+/// Resolves to xi-core's answer. Dropping it cancels the request, so the
+/// response is discarded when it arrives.
+struct ResponseFuture {
+    id: u64,
+    receiver: oneshot::Receiver<Result<Value, RequestError>>,
+    pending_requests: PendingRequests,
+}
+
+impl Future for ResponseFuture {
+    type Output = Result<Value, RequestError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match Pin::new(&mut self.receiver).poll(cx) {
+            Poll::Ready(Ok(result)) => Poll::Ready(result),
+            Poll::Ready(Err(oneshot::Canceled)) => Poll::Ready(Err(RequestError::Canceled)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
 }
 
-impl<F: FnOnce(Result<Value, Value>) + Send> Callback for F {
-    fn call(self: Box<Self>, result: Result<Value, Value>) {
-        (*self)(result)
+impl Drop for ResponseFuture {
+    fn drop(&mut self) {
+        self.pending_requests.lock().unwrap().remove(&self.id);
     }
 }
 
 pub struct Client {
     sender: XiSender,
-    pending_requests: Arc<Mutex<HashMap<u64, Box<dyn Callback>>>>,
-    current_request_id: Cell<u64>,
+    pending_requests: PendingRequests,
+    current_request_id: Arc<AtomicU64>,
+    rpc_sender: Option<Sender<RpcOperations>>,
+    recorder: Arc<Mutex<Option<Recorder>>>,
 }
 
 impl fmt::Debug for Client {
@@ -49,6 +84,8 @@ impl Clone for Client {
             sender: self.sender.clone(),
             pending_requests: self.pending_requests.clone(),
             current_request_id: self.current_request_id.clone(),
+            rpc_sender: self.rpc_sender.clone(),
+            recorder: self.recorder.clone(),
         }
     }
 }
@@ -60,13 +97,9 @@ impl Data for Client {
 }
 
 impl Default for Client {
+    /// A client that isn't connected to any core, until `main` replaces it.
     fn default() -> Self {
-        let (mut _receiver, sender) = Client::start_xi_thread();
-        Client {
-            sender,
-            pending_requests: Arc::new(Mutex::new(HashMap::new())),
-            current_request_id: Cell::new(0),
-        }
+        Client::with_sender(Box::new(io::sink()), None)
     }
 }
 
@@ -88,58 +121,69 @@ pub enum RpcOperations {
     AvailableLanguages(AvailableLanguages),
     LanguageChanged(LanguageChanged),
     MeasureWidth((u64, MeasureWidth)),
+    /// The text `Client::copy` or `Client::cut` took from the selection.
+    Copied(String),
+    /// The answer to `Client::open_view`.
+    ViewOpened {
+        view_id: ViewId,
+        file_path: String,
+    },
+    /// A notification this client does not model, e.g. from a newer xi-core.
+    Unknown {
+        method: String,
+        params: Value,
+    },
+    Error(ClientError),
 }
 
 impl Client {
+    /// Starts a client backed by an in-process xi-core.
     pub fn new() -> (Client, crossbeam_channel::Receiver<RpcOperations>) {
-        let (mut receiver, sender) = Client::start_xi_thread();
-        let client = Client {
-            sender,
-            pending_requests: Arc::new(Mutex::new(HashMap::new())),
-            current_request_id: Cell::new(0),
-        };
+        let (receiver, sender) = InProcessTransport::start();
+        Client::from_connection(receiver, sender)
+    }
+
+    /// Starts a client talking to xi-core over the given transport.
+    pub fn with_transport(
+        transport: Box<dyn Transport>,
+    ) -> Result<(Client, crossbeam_channel::Receiver<RpcOperations>), ClientError> {
+        let (receiver, sender) = transport.connect()?;
+        Ok(Client::from_connection(receiver, sender))
+    }
 
+    fn from_connection(
+        mut receiver: XiReader,
+        sender: XiWriter,
+    ) -> (Client, crossbeam_channel::Receiver<RpcOperations>) {
         let (rpc_sender, rpc_receiver) = unbounded();
-        let pending_requests = client.pending_requests.clone();
+        let client = Client::with_sender(sender, Some(rpc_sender));
 
+        let mut responder = client.clone();
         thread::spawn(move || {
             let mut buf = String::new();
-            while receiver.read_line(&mut buf).is_ok() {
-                let msg = match Message::decode(&buf) {
-                    Ok(x) => x,
-                    Err(err) => {
-                        log::info!("buf: {}", buf);
-                        panic!("{:?}", err);
-                    }
-                };
-                log::info!("Received message from xi: {:?}", msg);
-                match msg {
-                    Message::Request(res) => {
-                        let Request { method, params, id } = res;
-                        let operation = match method.as_str() {
-                            "measure_width" => RpcOperations::MeasureWidth((
-                                id,
-                                from_value::<MeasureWidth>(params).unwrap(),
-                            )),
-                            _ => {
-                                unreachable!("Unknown method {}", method);
-                            }
-                        };
-                        rpc_sender.send(operation).unwrap();
+            loop {
+                match receiver.read_line(&mut buf) {
+                    Ok(0) => {
+                        info!("xi-core closed the connection");
+                        break;
                     }
-                    Message::Response(res) => {
-                        let Response { id, result } = res;
-                        if let Some(cb) = pending_requests.lock().unwrap().remove(&id) {
-                            cb.call(result);
-                        }
+                    Ok(_) => {}
+                    Err(err) => {
+                        responder.report(ClientError::Transport(err));
+                        break;
                     }
-                    Message::Notification(res) => {
-                        let Notification { method, params } = res;
-                        let operation = Client::handle_notification(method, params);
-                        if let Err(err) = rpc_sender.send(operation) {
-                            log::error!("{}", err);
-                        };
+                }
+
+                match Message::decode(&buf) {
+                    Ok(msg) => {
+                        log::info!("Received message from xi: {:?}", msg);
+                        responder.record(Direction::FromCore, &msg);
+                        responder.handle_message(msg);
                     }
+                    Err(error) => responder.report(ClientError::Decode {
+                        line: buf.trim_end().to_string(),
+                        error,
+                    }),
                 }
 
                 buf.clear();
@@ -149,13 +193,95 @@ impl Client {
         (client, rpc_receiver)
     }
 
-    fn start_xi_thread() -> (XiReceiver, XiSender) {
-        let (to_core_rx, to_core_tx) = pipe();
-        let (from_core_rx, from_core_tx) = pipe();
-        let mut state = XiCore::new();
-        let mut rpc_looper = RpcLoop::new(from_core_tx);
-        thread::spawn(move || rpc_looper.mainloop(|| to_core_rx, &mut state));
-        (from_core_rx, to_core_tx)
+    fn with_sender(sender: XiWriter, rpc_sender: Option<Sender<RpcOperations>>) -> Client {
+        let client = Client {
+            sender: Arc::new(Mutex::new(sender)),
+            pending_requests: Arc::new(Mutex::new(HashMap::new())),
+            current_request_id: Arc::new(AtomicU64::new(0)),
+            rpc_sender,
+            recorder: Arc::new(Mutex::new(None)),
+        };
+
+        let pending_requests = Arc::downgrade(&client.pending_requests);
+        thread::spawn(move || Client::reap_pending_requests(pending_requests));
+
+        client
+    }
+
+    /// Fails requests whose deadline passed and drops the ones nobody waits
+    /// for anymore, until the client is gone.
+    fn reap_pending_requests(pending_requests: Weak<Mutex<HashMap<u64, PendingRequest>>>) {
+        loop {
+            thread::sleep(REAP_INTERVAL);
+            let pending_requests = match pending_requests.upgrade() {
+                Some(pending_requests) => pending_requests,
+                None => break,
+            };
+
+            let now = Instant::now();
+            let mut pending = pending_requests.lock().unwrap();
+            let stale: Vec<u64> = pending
+                .iter()
+                .filter(|(_, req)| req.deadline <= now || req.sender.is_canceled())
+                .map(|(id, _)| *id)
+                .collect();
+
+            for id in stale {
+                if let Some(req) = pending.remove(&id) {
+                    if !req.sender.is_canceled() {
+                        warn!("request {} to xi-core timed out", id);
+                        let _ = req.sender.send(Err(RequestError::Timeout));
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_message(&mut self, msg: Message) {
+        match msg {
+            Message::Request(req) => {
+                let Request { method, params, id } = req;
+                match Client::handle_request(method, params, id) {
+                    Ok(operation) => self.emit(operation),
+                    Err(err) => {
+                        // answer anyway, otherwise xi-core blocks waiting for us
+                        self.send_response(id, Err(json!({ "message": err.to_string() })));
+                        self.report(err);
+                    }
+                }
+            }
+            Message::Response(res) => {
+                let Response { id, result } = res;
+                let pending = self.pending_requests.lock().unwrap().remove(&id);
+                match pending {
+                    Some(req) => {
+                        let _ = req.sender.send(result.map_err(RequestError::Remote));
+                    }
+                    None => warn!("dropping response to expired or canceled request {}", id),
+                }
+            }
+            Message::Notification(notification) => {
+                let Notification { method, params } = notification;
+                match Client::handle_notification(method, params) {
+                    Ok(operation) => self.emit(operation),
+                    Err(err) => self.report(err),
+                }
+            }
+        }
+    }
+
+    fn emit(&self, operation: RpcOperations) {
+        if let Some(sender) = &self.rpc_sender {
+            if let Err(err) = sender.send(operation) {
+                log::error!("{}", err);
+            }
+        }
+    }
+
+    /// Logs the error and forwards it as `RpcOperations::Error`.
+    fn report(&self, err: ClientError) {
+        error!("{}", err);
+        self.emit(RpcOperations::Error(err));
     }
 
     pub fn client_started(
@@ -192,91 +318,356 @@ impl Client {
         )
     }
 
-    pub fn send_notification(&mut self, method: &str, params: &Value) {
-        let cmd = json!({
-            "method": method,
-            "params": params,
-        });
-
-        info!("Xi-CORE <-- {}", cmd);
-        self.sender.write_all(&to_vec(&cmd).unwrap()).unwrap();
-        self.sender.write_all(b"\n").unwrap();
-        self.sender.flush().unwrap();
+    /// Overrides the config of one view only.
+    pub fn modify_view_config(&mut self, view_id: &ViewId, changes: &Value) {
+        self.send_notification(
+            "modify_user_config",
+            &json!({
+                "domain": { "user_override": view_id },
+                "changes": changes,
+            }),
+        )
     }
 
-    pub fn new_view<F>(&mut self, file_path: String, callback: F)
-    where
-        F: FnOnce(Result<Value, Value>) + Send + 'static,
-    {
-        self.send_request(
-            "new_view",
+    /// xi-core writes the buffer itself and reports failures with `alert`.
+    pub fn save(&mut self, view_id: &ViewId, file_path: &str) {
+        self.send_notification(
+            "save",
             &json!({
+                "view_id": view_id,
                 "file_path": file_path,
             }),
-            callback,
         );
     }
 
-    /// Calls the callback with the result (from a different thread).
-    fn send_request<F>(&mut self, method: &str, params: &Value, callback: F)
-    where
-        F: FnOnce(Result<Value, Value>) + Send + 'static,
-    {
-        let cmd = json!({
-            "method": method,
-            "params": params,
-            "id": self.current_request_id,
+    pub fn start_plugin(&mut self, view_id: &ViewId, plugin_name: &str) {
+        self.send_notification(
+            "plugin",
+            &json!({
+                "command": "start",
+                "view_id": view_id,
+                "plugin_name": plugin_name,
+            }),
+        );
+    }
+
+    pub fn stop_plugin(&mut self, view_id: &ViewId, plugin_name: &str) {
+        self.send_notification(
+            "plugin",
+            &json!({
+                "command": "stop",
+                "view_id": view_id,
+                "plugin_name": plugin_name,
+            }),
+        );
+    }
+
+    /// Sends `rpc` to a plugin, e.g. the `rpc_cmd` of a command it
+    /// advertised through `update_cmds`.
+    pub fn plugin_rpc(&mut self, view_id: &ViewId, receiver: &str, rpc: &PluginRpc) {
+        self.send_notification(
+            "plugin",
+            &json!({
+                "command": "plugin_rpc",
+                "view_id": view_id,
+                "receiver": receiver,
+                "rpc": rpc,
+            }),
+        );
+    }
+
+    pub fn send_notification(&mut self, method: &str, params: &Value) {
+        let msg = Message::Notification(Notification {
+            method: method.to_string(),
+            params: params.clone(),
+        });
+
+        if let Err(err) = self.send_message(&msg) {
+            self.report(ClientError::Transport(err));
+        }
+    }
+
+    /// Answers a request xi-core sent us, such as `measure_width`.
+    pub fn send_response(&mut self, id: u64, result: Result<Value, Value>) {
+        let msg = Message::Response(Response { id, result });
+        if let Err(err) = self.send_message(&msg) {
+            self.report(ClientError::Transport(err));
+        }
+    }
+
+    /// Sends one of the `edit` commands to the given view.
+    pub fn edit(&mut self, view_id: &ViewId, cmd: EditCommand) {
+        let mut params = match to_value(&cmd) {
+            Ok(params) => params,
+            Err(error) => {
+                let method = "edit".to_string();
+                self.report(ClientError::InvalidParams { method, error });
+                return;
+            }
+        };
+        params["view_id"] = json!(view_id);
+        self.send_notification("edit", &params);
+    }
+
+    /// Opens a view without blocking; the outcome arrives as
+    /// `RpcOperations::ViewOpened` or `RpcOperations::Error`.
+    pub fn open_view(&self, file_path: String) {
+        let client = self.clone();
+        thread::spawn(move || match block_on(client.new_view(file_path.clone())) {
+            Ok(view_id) => client.emit(RpcOperations::ViewOpened { view_id, file_path }),
+            Err(error) => client.report(ClientError::Request {
+                method: "new_view".to_string(),
+                error,
+            }),
         });
-        let id = { self.current_request_id.get() };
-        info!("Xi-CORE <-- {}", cmd.clone());
-        self.sender.write_all(&to_vec(&cmd).unwrap()).unwrap();
-        self.sender.write_all(b"\n").unwrap();
-        self.sender.flush().unwrap();
+    }
+
+    /// Asks xi-core for the selected text without blocking; a non-empty
+    /// selection arrives as `RpcOperations::Copied`.
+    pub fn copy(&self, view_id: &ViewId) {
+        self.take_selection(view_id, "copy");
+    }
+
+    /// Like `copy`, and deletes the selection too.
+    pub fn cut(&self, view_id: &ViewId) {
+        self.take_selection(view_id, "cut");
+    }
+
+    fn take_selection(&self, view_id: &ViewId, method: &'static str) {
+        let client = self.clone();
+        let params = json!({ "method": method, "view_id": view_id });
+        thread::spawn(move || match block_on(client.request("edit", params)) {
+            Ok(Value::String(text)) => client.emit(RpcOperations::Copied(text)),
+            Ok(_) => {}
+            Err(error) => client.report(ClientError::Request {
+                method: method.to_string(),
+                error,
+            }),
+        });
+    }
+
+    pub async fn new_view(&self, file_path: String) -> Result<ViewId, RequestError> {
+        let result = self
+            .request("new_view", json!({ "file_path": file_path }))
+            .await?;
+        from_value(result).map_err(RequestError::InvalidResult)
+    }
+
+    /// Sends a request and resolves to its result, or to `RequestError::Timeout`
+    /// after `REQUEST_TIMEOUT`.
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value, RequestError> {
+        self.request_with_timeout(method, params, REQUEST_TIMEOUT)
+            .await
+    }
+
+    pub async fn request_with_timeout(
+        &self,
+        method: &str,
+        params: Value,
+        timeout: Duration,
+    ) -> Result<Value, RequestError> {
+        self.start_request(method, params, timeout)?.await
+    }
+
+    fn start_request(
+        &self,
+        method: &str,
+        params: Value,
+        timeout: Duration,
+    ) -> Result<ResponseFuture, RequestError> {
+        let id = self.current_request_id.fetch_add(1, Ordering::SeqCst);
+        let (sender, receiver) = oneshot::channel();
+        let deadline = Instant::now() + timeout;
+
+        // register before writing, the response may arrive before we return
         self.pending_requests
             .lock()
             .unwrap()
-            .insert(id, Box::new(callback));
-        self.current_request_id.set(id + 1);
+            .insert(id, PendingRequest { deadline, sender });
+        let response = ResponseFuture {
+            id,
+            receiver,
+            pending_requests: self.pending_requests.clone(),
+        };
+
+        let msg = Message::Request(Request {
+            id,
+            method: method.to_string(),
+            params,
+        });
+        self.send_message(&msg).map_err(RequestError::Transport)?;
+
+        Ok(response)
+    }
+
+    /// Starts writing every message in both directions to `recorder`.
+    pub fn start_recording(&self, recorder: Recorder) {
+        *self.recorder.lock().unwrap() = Some(recorder);
+    }
+
+    fn record(&self, direction: Direction, msg: &Message) {
+        if let Some(recorder) = self.recorder.lock().unwrap().as_mut() {
+            if let Err(err) = recorder.record(direction, msg) {
+                error!("failed to record rpc message: {}", err);
+            }
+        }
     }
 
-    pub fn handle_notification(method: String, params: Value) -> RpcOperations {
+    fn send_message(&self, msg: &Message) -> io::Result<()> {
+        let bytes = msg.to_vec();
+        info!("Xi-CORE <-- {}", String::from_utf8_lossy(&bytes));
+        self.record(Direction::ToCore, msg);
+
+        let mut sender = self.sender.lock().unwrap();
+        sender.write_all(&bytes)?;
+        sender.write_all(b"\n")?;
+        sender.flush()
+    }
+
+    pub fn handle_request(
+        method: String,
+        params: Value,
+        id: u64,
+    ) -> Result<RpcOperations, ClientError> {
         match method.as_str() {
-            "update" => RpcOperations::Update(from_value::<Update>(params).unwrap()),
-            "scroll_to" => RpcOperations::ScrollTo(from_value::<ScrollTo>(params).unwrap()),
-            "def_style" => RpcOperations::DefStyle(from_value::<Style>(params).unwrap()),
+            "measure_width" => Ok(RpcOperations::MeasureWidth((
+                id,
+                Client::parse_params(&method, params)?,
+            ))),
+            _ => Err(ClientError::UnknownMethod(method)),
+        }
+    }
+
+    pub fn handle_notification(
+        method: String,
+        params: Value,
+    ) -> Result<RpcOperations, ClientError> {
+        let operation = match method.as_str() {
+            "update" => RpcOperations::Update(Client::parse_params(&method, params)?),
+            "scroll_to" => RpcOperations::ScrollTo(Client::parse_params(&method, params)?),
+            "def_style" => RpcOperations::DefStyle(Client::parse_params(&method, params)?),
             "available_plugins" => {
-                RpcOperations::AvailablePlugins(from_value::<AvailablePlugins>(params).unwrap())
+                RpcOperations::AvailablePlugins(Client::parse_params(&method, params)?)
             }
             "plugin_started" => {
-                RpcOperations::PluginStarted(from_value::<PluginStarted>(params).unwrap())
+                RpcOperations::PluginStarted(Client::parse_params(&method, params)?)
             }
             "plugin_stopped" => {
-                RpcOperations::PluginStopped(from_value::<PluginStopped>(params).unwrap())
+                RpcOperations::PluginStopped(Client::parse_params(&method, params)?)
             }
-            "update_cmds" => RpcOperations::UpdateCmds(from_value::<UpdateCmds>(params).unwrap()),
+            "update_cmds" => RpcOperations::UpdateCmds(Client::parse_params(&method, params)?),
             "config_changed" => {
-                RpcOperations::ConfigChanged(from_value::<ConfigChanged>(params).unwrap())
-            }
-            "theme_changed" => {
-                RpcOperations::ThemeChanged(from_value::<ThemeChanged>(params).unwrap())
+                RpcOperations::ConfigChanged(Client::parse_params(&method, params)?)
             }
-            "alert" => RpcOperations::Alert(from_value::<Alert>(params).unwrap()),
+            "theme_changed" => RpcOperations::ThemeChanged(Client::parse_params(&method, params)?),
+            "alert" => RpcOperations::Alert(Client::parse_params(&method, params)?),
             "available_themes" => {
-                RpcOperations::AvailableThemes(from_value::<AvailableThemes>(params).unwrap())
+                RpcOperations::AvailableThemes(Client::parse_params(&method, params)?)
             }
-            "find_status" => RpcOperations::FindStatus(from_value::<FindStatus>(params).unwrap()),
+            "find_status" => RpcOperations::FindStatus(Client::parse_params(&method, params)?),
             "replace_status" => {
-                RpcOperations::ReplaceStatus(from_value::<ReplaceStatus>(params).unwrap())
+                RpcOperations::ReplaceStatus(Client::parse_params(&method, params)?)
             }
             "available_languages" => {
-                RpcOperations::AvailableLanguages(from_value::<AvailableLanguages>(params).unwrap())
+                RpcOperations::AvailableLanguages(Client::parse_params(&method, params)?)
             }
             "language_changed" => {
-                RpcOperations::LanguageChanged(from_value::<LanguageChanged>(params).unwrap())
+                RpcOperations::LanguageChanged(Client::parse_params(&method, params)?)
             }
-            _ => {
-                unreachable!("Unknown method {}", method)
+            _ => RpcOperations::Unknown { method, params },
+        };
+        Ok(operation)
+    }
+
+    fn parse_params<T: DeserializeOwned>(method: &str, params: Value) -> Result<T, ClientError> {
+        from_value::<T>(params).map_err(|error| ClientError::InvalidParams {
+            method: method.to_string(),
+            error,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offline_client() -> Client {
+        let (client, _rpc_receiver) =
+            Client::from_connection(Box::new(io::empty()), Box::new(io::sink()));
+        client
+    }
+
+    #[test]
+    fn dropping_a_request_cancels_it() {
+        let client = offline_client();
+        block_on(async {
+            let mut response = Box::pin(client.request("new_view", json!({})));
+            assert!(futures::poll!(response.as_mut()).is_pending());
+            assert_eq!(1, client.pending_requests.lock().unwrap().len());
+
+            drop(response);
+            assert!(client.pending_requests.lock().unwrap().is_empty());
+        });
+    }
+
+    #[test]
+    fn unanswered_request_times_out() {
+        let client = offline_client();
+        let timeout = Duration::from_millis(10);
+        let result = block_on(client.request_with_timeout("new_view", json!({}), timeout));
+
+        assert!(matches!(result, Err(RequestError::Timeout)));
+        assert!(client.pending_requests.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn response_resolves_matching_request() {
+        let client = offline_client();
+        block_on(async {
+            let mut first = Box::pin(client.request("new_view", json!({})));
+            let mut second = Box::pin(client.request("new_view", json!({})));
+            assert!(futures::poll!(first.as_mut()).is_pending());
+            assert!(futures::poll!(second.as_mut()).is_pending());
+
+            let mut responder = client.clone();
+            responder.handle_message(Message::Response(Response {
+                id: 1,
+                result: Ok(json!("view-id-2")),
+            }));
+
+            assert_eq!(json!("view-id-2"), second.await.unwrap());
+            assert!(futures::poll!(first.as_mut()).is_pending());
+        });
+    }
+
+    #[test]
+    fn unknown_notification_is_forwarded() {
+        let params = json!({ "view_id": "view-id-1" });
+        let operation = Client::handle_notification("new_fancy_thing".to_string(), params.clone());
+        match operation {
+            Ok(RpcOperations::Unknown { method, params: p }) => {
+                assert_eq!(method, "new_fancy_thing");
+                assert_eq!(p, params);
             }
+            other => panic!("unexpected operation: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn invalid_params_are_reported() {
+        let operation = Client::handle_notification("alert".to_string(), json!({ "foo": 1 }));
+        match operation {
+            Err(ClientError::InvalidParams { method, .. }) => assert_eq!(method, "alert"),
+            other => panic!("unexpected operation: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unknown_request_is_an_error() {
+        let operation = Client::handle_request("unknown".to_string(), json!({}), 1);
+        match operation {
+            Err(ClientError::UnknownMethod(method)) => assert_eq!(method, "unknown"),
+            other => panic!("unexpected operation: {:?}", other),
         }
     }
 }
@@ -4,6 +4,7 @@
 
 use serde_json::error::Category;
 use serde_json::error::Error as SerdeError;
+use serde_json::Value;
 use std::error::Error;
 use std::fmt::{self, Display, Formatter};
 use std::io;
@@ -17,19 +18,15 @@ pub enum DecodeError {
 
 impl Display for DecodeError {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
-        write!(f, "{}", self)
-    }
-}
-
-impl Error for DecodeError {
-    fn description(&self) -> &str {
         match *self {
-            DecodeError::Truncated => "not enough bytes to decode a complete message",
-            DecodeError::Io(_) => "failure to read or write bytes on an IO stream",
-            DecodeError::InvalidJson => "the byte sequence is not valid JSON",
+            DecodeError::Truncated => write!(f, "not enough bytes to decode a complete message"),
+            DecodeError::Io(ref err) => write!(f, "failure to read bytes on an IO stream: {}", err),
+            DecodeError::InvalidJson => write!(f, "the byte sequence is not valid JSON"),
         }
     }
+}
 
+impl Error for DecodeError {
     fn cause(&self) -> Option<&dyn Error> {
         if let DecodeError::Io(ref io_err) = *self {
             Some(io_err)
@@ -48,3 +45,101 @@ impl From<SerdeError> for DecodeError {
         }
     }
 }
+
+/// Everything that can go wrong between the `Client` and xi-core.
+///
+/// None of these are fatal: they are reported through `RpcOperations::Error`
+/// so the editor can log them and keep running.
+#[derive(Debug)]
+pub enum ClientError {
+    /// A line received from xi-core is not a valid JSON-RPC message.
+    Decode { line: String, error: DecodeError },
+    /// Reading from or writing to xi-core failed.
+    Transport(io::Error),
+    /// A known method arrived with params we could not deserialize.
+    InvalidParams { method: String, error: SerdeError },
+    /// xi-core sent a request we have no handler for.
+    UnknownMethod(String),
+    /// A request made in the background failed.
+    Request { method: String, error: RequestError },
+}
+
+impl Display for ClientError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match *self {
+            ClientError::Decode {
+                ref line,
+                ref error,
+            } => write!(
+                f,
+                "failed to decode message from xi-core ({}): {}",
+                error, line
+            ),
+            ClientError::Transport(ref err) => write!(f, "xi-core transport error: {}", err),
+            ClientError::InvalidParams {
+                ref method,
+                ref error,
+            } => write!(f, "invalid params for `{}`: {}", method, error),
+            ClientError::UnknownMethod(ref method) => write!(f, "unknown method `{}`", method),
+            ClientError::Request {
+                ref method,
+                ref error,
+            } => write!(f, "request `{}` failed: {}", method, error),
+        }
+    }
+}
+
+impl Error for ClientError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            ClientError::Decode { ref error, .. } => Some(error),
+            ClientError::Transport(ref err) => Some(err),
+            ClientError::InvalidParams { ref error, .. } => Some(error),
+            ClientError::UnknownMethod(_) => None,
+            ClientError::Request { ref error, .. } => Some(error),
+        }
+    }
+}
+
+impl From<io::Error> for ClientError {
+    fn from(err: io::Error) -> ClientError {
+        ClientError::Transport(err)
+    }
+}
+
+/// Why `Client::request` did not produce a result.
+#[derive(Debug)]
+pub enum RequestError {
+    /// xi-core answered with an error object.
+    Remote(Value),
+    /// The result doesn't have the shape the caller expected.
+    InvalidResult(SerdeError),
+    /// The request could not be written to xi-core.
+    Transport(io::Error),
+    /// No response arrived before the deadline.
+    Timeout,
+    /// The client went away before the response arrived.
+    Canceled,
+}
+
+impl Display for RequestError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match *self {
+            RequestError::Remote(ref value) => write!(f, "xi-core returned an error: {}", value),
+            RequestError::InvalidResult(ref err) => write!(f, "unexpected result: {}", err),
+            RequestError::Transport(ref err) => write!(f, "xi-core transport error: {}", err),
+            RequestError::Timeout => write!(f, "timed out waiting for xi-core"),
+            RequestError::Canceled => write!(f, "request was canceled"),
+        }
+    }
+}
+
+impl Error for RequestError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            RequestError::InvalidResult(ref err) => Some(err),
+            RequestError::Transport(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
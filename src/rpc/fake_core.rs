@@ -0,0 +1,370 @@
+//! A scriptable stand-in for xi-core, so the client and everything behind
+//! `RpcOperations` can be tested without a real core or a window.
+//!
+//! ```ignore
+//! let core = FakeCore::new()
+//!     .on_request("new_view", json!("view-id-1"))
+//!     .then_notify("update", json!({ "view_id": "view-id-1", "update": { .. } }));
+//! let received = core.received();
+//! let (client, operations) = Client::with_transport(Box::new(core))?;
+//! ```
+
+use std::io::{self, BufRead, Write};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use log::*;
+use pipe::{pipe, PipeWriter};
+use serde_json::{json, Value};
+
+use crate::rpc::message::{Message, Notification, Request, Response};
+use crate::rpc::transport::{Transport, XiReader, XiWriter};
+
+/// Everything the client sent to a `FakeCore`, in order.
+pub type Received = Arc<Mutex<Vec<Message>>>;
+
+struct Rule {
+    method: String,
+    /// `Some` for requests, answered with this result.
+    result: Option<Result<Value, Value>>,
+    then: Vec<Message>,
+}
+
+impl Rule {
+    fn matches(&self, msg: &Message) -> bool {
+        match msg {
+            Message::Request(req) => self.result.is_some() && req.method == self.method,
+            Message::Notification(n) => self.result.is_none() && n.method == self.method,
+            Message::Response(_) => false,
+        }
+    }
+}
+
+/// A core that answers from a script. Each rule fires once, for the first
+/// matching message; requests nobody scripted get an error response so the
+/// client never waits for a timeout.
+pub struct FakeCore {
+    startup: Vec<Message>,
+    rules: Vec<Rule>,
+    received: Received,
+}
+
+impl FakeCore {
+    pub fn new() -> FakeCore {
+        FakeCore {
+            startup: vec![],
+            rules: vec![],
+            received: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    /// Sends a notification as soon as the client connects.
+    pub fn notify(mut self, method: &str, params: Value) -> Self {
+        self.startup.push(notification(method, params));
+        self
+    }
+
+    /// Answers the next `method` request with `result`.
+    pub fn on_request(self, method: &str, result: Value) -> Self {
+        self.rule(method, Some(Ok(result)))
+    }
+
+    /// Fails the next `method` request with `error`.
+    pub fn on_request_error(self, method: &str, error: Value) -> Self {
+        self.rule(method, Some(Err(error)))
+    }
+
+    /// Waits for a `method` notification, to hang `then_*` messages on.
+    pub fn on_notification(self, method: &str) -> Self {
+        self.rule(method, None)
+    }
+
+    /// Sends a notification after the previous rule fired.
+    pub fn then_notify(mut self, method: &str, params: Value) -> Self {
+        self.last_rule().then.push(notification(method, params));
+        self
+    }
+
+    /// Sends a request after the previous rule fired. The client's answer
+    /// ends up in `received`.
+    pub fn then_request(mut self, id: u64, method: &str, params: Value) -> Self {
+        let method = method.to_string();
+        let request = Message::Request(Request { id, method, params });
+        self.last_rule().then.push(request);
+        self
+    }
+
+    pub fn received(&self) -> Received {
+        self.received.clone()
+    }
+
+    fn rule(mut self, method: &str, result: Option<Result<Value, Value>>) -> Self {
+        self.rules.push(Rule {
+            method: method.to_string(),
+            result,
+            then: vec![],
+        });
+        self
+    }
+
+    fn last_rule(&mut self) -> &mut Rule {
+        self.rules
+            .last_mut()
+            .expect("then_* needs an on_* rule before it")
+    }
+
+    fn run(mut self, mut reader: impl BufRead, mut writer: PipeWriter) -> io::Result<()> {
+        for msg in self.startup.drain(..) {
+            write_message(&mut writer, &msg)?;
+        }
+
+        let mut buf = String::new();
+        while reader.read_line(&mut buf)? > 0 {
+            let msg = match Message::decode(&buf) {
+                Ok(msg) => msg,
+                Err(err) => {
+                    error!("fake core can't decode {:?}: {}", buf, err);
+                    buf.clear();
+                    continue;
+                }
+            };
+            buf.clear();
+            self.received.lock().unwrap().push(msg.clone());
+
+            match self.rules.iter().position(|rule| rule.matches(&msg)) {
+                Some(index) => {
+                    let rule = self.rules.remove(index);
+                    if let (Message::Request(req), Some(result)) = (&msg, rule.result) {
+                        let response = Message::Response(Response { id: req.id, result });
+                        write_message(&mut writer, &response)?;
+                    }
+                    for msg in rule.then.iter() {
+                        write_message(&mut writer, msg)?;
+                    }
+                }
+                None => {
+                    if let Message::Request(req) = &msg {
+                        let error = json!({ "message": format!("unscripted `{}`", req.method) });
+                        let response = Message::Response(Response {
+                            id: req.id,
+                            result: Err(error),
+                        });
+                        write_message(&mut writer, &response)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Transport for FakeCore {
+    fn connect(self: Box<Self>) -> io::Result<(XiReader, XiWriter)> {
+        let (to_core_rx, to_core_tx) = pipe();
+        let (from_core_rx, from_core_tx) = pipe();
+        thread::spawn(move || {
+            if let Err(err) = self.run(to_core_rx, from_core_tx) {
+                debug!("fake core stopped: {}", err);
+            }
+        });
+        Ok((Box::new(from_core_rx), Box::new(to_core_tx)))
+    }
+}
+
+fn notification(method: &str, params: Value) -> Message {
+    Message::Notification(Notification {
+        method: method.to_string(),
+        params,
+    })
+}
+
+fn write_message(writer: &mut impl Write, msg: &Message) -> io::Result<()> {
+    writer.write_all(&msg.to_vec())?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linecache::LineCache;
+    use crate::rpc::client::{Client, RpcOperations};
+    use crate::rpc::errors::{ClientError, RequestError};
+    use futures::executor::block_on;
+    use std::time::Duration;
+
+    const WAIT: Duration = Duration::from_secs(5);
+
+    fn update(ops: Value) -> Value {
+        json!({
+            "view_id": "view-id-1",
+            "update": { "ops": ops, "pristine": true, "rev": 1 }
+        })
+    }
+
+    #[test]
+    fn new_view_then_updates_fill_line_cache() {
+        let core = FakeCore::new()
+            .on_request("new_view", json!("view-id-1"))
+            .then_notify(
+                "update",
+                update(json!([
+                    { "op": "ins", "n": 2, "lines": [
+                        { "text": "fn main() {\n", "ln": 1 },
+                        { "text": "}\n", "ln": 2 }
+                    ]}
+                ])),
+            )
+            .then_notify(
+                "update",
+                update(json!([
+                    { "op": "copy", "n": 1, "ln": 1 },
+                    { "op": "ins", "n": 1, "lines": [{ "text": "    println!();\n", "ln": 2 }] },
+                    { "op": "copy", "n": 1, "ln": 3 }
+                ])),
+            );
+        let received = core.received();
+        let (client, operations) = Client::with_transport(Box::new(core)).unwrap();
+
+        let view_id = block_on(client.new_view("main.rs".to_string())).unwrap();
+        assert_eq!("view-id-1", view_id.to_string());
+
+        let mut line_cache = LineCache::new();
+        for _ in 0..2 {
+            match operations.recv_timeout(WAIT).unwrap() {
+                RpcOperations::Update(update) => {
+                    line_cache.update(update);
+                }
+                other => panic!("unexpected operation: {:?}", other),
+            }
+        }
+
+        let text: Vec<&str> = (0..line_cache.height())
+            .map(|n| line_cache.get_line(n).unwrap().text.as_str())
+            .collect();
+        assert_eq!(vec!["fn main() {\n", "    println!();\n", "}\n"], text);
+
+        let received = received.lock().unwrap();
+        match &received[0] {
+            Message::Request(req) => {
+                assert_eq!("new_view", req.method);
+                assert_eq!(json!({ "file_path": "main.rs" }), req.params);
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn startup_notifications_and_errors() {
+        let core = FakeCore::new()
+            .notify(
+                "available_themes",
+                json!({ "themes": ["base16-eighties.dark"] }),
+            )
+            .on_request_error("new_view", json!({ "message": "no such file" }));
+        let (client, operations) = Client::with_transport(Box::new(core)).unwrap();
+
+        match operations.recv_timeout(WAIT).unwrap() {
+            RpcOperations::AvailableThemes(themes) => assert_eq!(1, themes.themes.len()),
+            other => panic!("unexpected operation: {:?}", other),
+        }
+
+        let result = block_on(client.new_view("missing.rs".to_string()));
+        assert!(matches!(result, Err(RequestError::Remote(_))));
+
+        let result = block_on(client.request("unscripted", json!({})));
+        assert!(matches!(result, Err(RequestError::Remote(_))));
+    }
+
+    #[test]
+    fn copy_and_cut_report_the_selection() {
+        let core = FakeCore::new()
+            .on_request("edit", json!("fn main"))
+            .on_request("edit", json!("}"))
+            .on_request("edit", Value::Null);
+        let received = core.received();
+        let (client, operations) = Client::with_transport(Box::new(core)).unwrap();
+        let view_id = "view-id-1".parse().unwrap();
+
+        client.copy(&view_id);
+        match operations.recv_timeout(WAIT).unwrap() {
+            RpcOperations::Copied(text) => assert_eq!("fn main", text),
+            other => panic!("unexpected operation: {:?}", other),
+        }
+
+        client.cut(&view_id);
+        match operations.recv_timeout(WAIT).unwrap() {
+            RpcOperations::Copied(text) => assert_eq!("}", text),
+            other => panic!("unexpected operation: {:?}", other),
+        }
+
+        // nothing selected, nothing to put on the clipboard
+        client.copy(&view_id);
+        assert!(operations.recv_timeout(Duration::from_millis(200)).is_err());
+
+        let received = received.lock().unwrap();
+        let methods: Vec<&Value> = received
+            .iter()
+            .filter_map(|msg| match msg {
+                Message::Request(req) => Some(&req.params["method"]),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(vec!["copy", "cut", "copy"], methods);
+    }
+
+    #[test]
+    fn open_view_reports_outcome() {
+        let core = FakeCore::new()
+            .on_request("new_view", json!("view-id-3"))
+            .on_request_error("new_view", json!({ "message": "no such file" }));
+        let (client, operations) = Client::with_transport(Box::new(core)).unwrap();
+
+        client.open_view("a.rs".to_string());
+        match operations.recv_timeout(WAIT).unwrap() {
+            RpcOperations::ViewOpened { view_id, file_path } => {
+                assert_eq!("view-id-3", view_id.to_string());
+                assert_eq!("a.rs", file_path);
+            }
+            other => panic!("unexpected operation: {:?}", other),
+        }
+
+        client.open_view("b.rs".to_string());
+        match operations.recv_timeout(WAIT).unwrap() {
+            RpcOperations::Error(ClientError::Request { method, .. }) => {
+                assert_eq!("new_view", method)
+            }
+            other => panic!("unexpected operation: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn core_requests_are_answered() {
+        let core = FakeCore::new()
+            .on_notification("client_started")
+            .then_request(7, "measure_width", json!([{ "id": 0, "strings": ["a"] }]));
+        let received = core.received();
+        let (mut client, operations) = Client::with_transport(Box::new(core)).unwrap();
+        client.client_started(None, None);
+
+        match operations.recv_timeout(WAIT).unwrap() {
+            RpcOperations::MeasureWidth((id, _)) => client.send_response(id, Ok(json!([[7.0]]))),
+            other => panic!("unexpected operation: {:?}", other),
+        }
+
+        for _ in 0..100 {
+            if received.lock().unwrap().len() == 2 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let received = received.lock().unwrap();
+        match &received[1] {
+            Message::Response(res) => {
+                assert_eq!(7, res.id);
+                assert_eq!(Ok(json!([[7.0]])), res.result);
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+}
@@ -1,11 +1,18 @@
 pub mod client;
 pub mod errors;
+#[cfg(test)]
+pub mod fake_core;
 pub mod message;
+pub mod recorder;
 pub mod structs;
+pub mod transport;
 
+pub use client::RpcOperations;
+pub use errors::ClientError;
 pub use structs::{
-    Alert, AvailableLanguages, AvailablePlugins, AvailableThemes, ConfigChanged, ConfigChanges,
-    FindStatus, LanguageChanged, Line, MeasureWidth, ModifySelection, Operation, OperationType,
-    PluginStarted, PluginStopped, Position, Query, ReplaceStatus, RpcOperations, ScrollTo, Status,
-    Style, StyleDef, ThemeChanged, ThemeSettings, Update, UpdateCmds, ViewId,
+    Alert, Annotation, AnnotationType, AvailableLanguages, AvailablePlugins, AvailableThemes,
+    ConfigChanged, ConfigChanges, EditCommand, FindStatus, GestureType, LanguageChanged, Line,
+    MeasureWidth, ModifySelection, Operation, OperationType, PluginStarted, PluginStopped,
+    Position, Query, ReplaceStatus, ScrollTo, Status, Style, StyleDef, ThemeChanged, ThemeSettings,
+    Update, UpdateCmds, ViewId,
 };
@@ -0,0 +1,188 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Cursor, LineWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crossbeam_channel::Receiver;
+use log::*;
+use serde::{Deserialize, Serialize};
+
+use crate::rpc::client::{Client, RpcOperations};
+use crate::rpc::errors::ClientError;
+use crate::rpc::message::Message;
+use crate::rpc::transport::{Transport, XiReader, XiWriter};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    ToCore,
+    FromCore,
+}
+
+impl Direction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Direction::ToCore => "to_core",
+            Direction::FromCore => "from_core",
+        }
+    }
+}
+
+/// One line of a recording.
+#[derive(Deserialize, Debug)]
+pub struct Entry {
+    /// Milliseconds since the Unix epoch.
+    pub time: u64,
+    pub direction: Direction,
+    pub message: Message,
+}
+
+/// Writes every rpc message, in both directions, as one JSON object per line.
+pub struct Recorder {
+    path: PathBuf,
+    writer: LineWriter<File>,
+}
+
+impl Recorder {
+    /// Creates `rpc-<unix millis>-<pid>.jsonl` inside `dir`, adding a counter
+    /// to the name rather than overwriting another recording.
+    pub fn create(dir: &Path) -> io::Result<Recorder> {
+        fs::create_dir_all(dir)?;
+        let stem = format!("rpc-{}-{}", now_millis(), std::process::id());
+        let mut attempt = 0;
+        loop {
+            let path = match attempt {
+                0 => dir.join(format!("{}.jsonl", stem)),
+                n => dir.join(format!("{}-{}.jsonl", stem, n)),
+            };
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => {
+                    info!("recording rpc messages to {:?}", path);
+                    let writer = LineWriter::new(file);
+                    return Ok(Recorder { path, writer });
+                }
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => attempt += 1,
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&mut self, direction: Direction, msg: &Message) -> io::Result<()> {
+        let mut line = format!(
+            "{{\"time\":{},\"direction\":\"{}\",\"message\":",
+            now_millis(),
+            direction.as_str()
+        )
+        .into_bytes();
+        line.extend(msg.to_vec());
+        line.extend(b"}\n");
+        self.writer.write_all(&line)
+    }
+}
+
+/// Reads all entries of a recording, skipping lines that don't parse.
+pub fn read_recording(path: &Path) -> io::Result<Vec<Entry>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut entries = vec![];
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Entry>(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(err) => warn!("{:?}:{}: skipping entry: {}", path, index + 1, err),
+        }
+    }
+    Ok(entries)
+}
+
+/// Plays back what xi-core sent in a recording, without a live core.
+/// Everything written to it is discarded.
+pub struct ReplayTransport {
+    pub path: PathBuf,
+}
+
+impl Transport for ReplayTransport {
+    fn connect(self: Box<Self>) -> io::Result<(XiReader, XiWriter)> {
+        let mut buf = vec![];
+        for entry in read_recording(&self.path)? {
+            if entry.direction == Direction::FromCore {
+                buf.extend(entry.message.to_vec());
+                buf.push(b'\n');
+            }
+        }
+        Ok((Box::new(Cursor::new(buf)), Box::new(io::sink())))
+    }
+}
+
+/// Feeds a recording into a fresh `RpcOperations` channel, for reproducing
+/// a session headlessly.
+pub fn replay(path: &Path) -> Result<(Client, Receiver<RpcOperations>), ClientError> {
+    Client::with_transport(Box::new(ReplayTransport {
+        path: path.to_path_buf(),
+    }))
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::message::Notification;
+    use serde_json::json;
+
+    #[test]
+    fn record_and_replay() {
+        let dir = std::env::temp_dir().join(format!("print-recorder-{}", now_millis()));
+        let mut recorder = Recorder::create(&dir).unwrap();
+
+        let outgoing = Message::Notification(Notification {
+            method: "set_theme".to_string(),
+            params: json!({ "theme_name": "InspiredGitHub" }),
+        });
+        let incoming = Message::Notification(Notification {
+            method: "available_themes".to_string(),
+            params: json!({ "themes": ["InspiredGitHub"] }),
+        });
+        recorder.record(Direction::ToCore, &outgoing).unwrap();
+        recorder.record(Direction::FromCore, &incoming).unwrap();
+
+        let entries = read_recording(recorder.path()).unwrap();
+        assert_eq!(2, entries.len());
+        assert_eq!(Direction::ToCore, entries[0].direction);
+        assert_eq!(Direction::FromCore, entries[1].direction);
+
+        let (_client, receiver) = replay(recorder.path()).unwrap();
+        match receiver.recv().unwrap() {
+            RpcOperations::AvailableThemes(themes) => {
+                assert_eq!(vec!["InspiredGitHub".to_string()], themes.themes)
+            }
+            other => panic!("unexpected operation: {:?}", other),
+        }
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn recordings_never_overwrite_each_other() {
+        let dir = std::env::temp_dir().join(format!("print-recordings-{}", now_millis()));
+        let first = Recorder::create(&dir).unwrap();
+        let second = Recorder::create(&dir).unwrap();
+        assert_ne!(first.path(), second.path());
+
+        let name = first.path().file_name().unwrap().to_str().unwrap();
+        assert!(name.contains(&format!("-{}", std::process::id())));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Deserializer};
+use serde_json as json;
+
+/// What an annotation marks. xi-core sends plugin defined types by name.
+#[derive(Debug, PartialEq, Clone)]
+pub enum AnnotationType {
+    Selection,
+    Find,
+    Other(String),
+}
+
+impl<'de> Deserialize<'de> for AnnotationType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        Ok(match name.as_str() {
+            "selection" => AnnotationType::Selection,
+            "find" => AnnotationType::Find,
+            _ => AnnotationType::Other(name),
+        })
+    }
+}
+
+/// The annotations of one type in the lines an `update` covers.
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+pub struct Annotation {
+    #[serde(rename = "type")]
+    pub annotation_type: AnnotationType,
+    /// `[start_line, start_col, end_line, end_col]`, columns being byte
+    /// offsets into their line.
+    pub ranges: Vec<[u64; 4]>,
+    /// One per range when the type carries data, e.g. diagnostics.
+    #[serde(default)]
+    pub payloads: Option<Vec<json::Value>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn deserialize_annotations() {
+        let value =
+            json!({"type": "selection", "ranges": [[0, 2, 1, 0]], "payloads": null, "n": 1});
+        let annotation: Annotation = serde_json::from_value(value).unwrap();
+        assert_eq!(AnnotationType::Selection, annotation.annotation_type);
+        assert_eq!(vec![[0, 2, 1, 0]], annotation.ranges);
+        assert_eq!(None, annotation.payloads);
+
+        let value =
+            json!({"type": "lint", "ranges": [[4, 0, 4, 3]], "payloads": [{"msg": "unused"}]});
+        let annotation: Annotation = serde_json::from_value(value).unwrap();
+        assert_eq!(
+            AnnotationType::Other("lint".to_string()),
+            annotation.annotation_type
+        );
+        assert_eq!(Some(vec![json!({"msg": "unused"})]), annotation.payloads);
+    }
+}
@@ -11,7 +11,7 @@ pub struct ConfigChanged {
     pub changes: ConfigChanges,
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize, Default)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, Default, Clone)]
 pub struct ConfigChanges {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub font_face: Option<String>,
@@ -27,4 +27,58 @@ pub struct ConfigChanges {
     pub translate_tabs_to_spaces: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub word_wrap: Option<bool>,
+    /// The column xi-core wraps at while `word_wrap` is off, 0 for none.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wrap_width: Option<u64>,
+}
+
+impl ConfigChanges {
+    /// Overrides the fields set in `changes`, xi-core only sends what changed.
+    pub fn apply(&mut self, changes: &ConfigChanges) {
+        if changes.font_face.is_some() {
+            self.font_face = changes.font_face.clone();
+        }
+        if changes.font_size.is_some() {
+            self.font_size = changes.font_size;
+        }
+        if changes.line_ending.is_some() {
+            self.line_ending = changes.line_ending.clone();
+        }
+        if changes.plugin_search_path.is_some() {
+            self.plugin_search_path = changes.plugin_search_path.clone();
+        }
+        if changes.tab_size.is_some() {
+            self.tab_size = changes.tab_size;
+        }
+        if changes.translate_tabs_to_spaces.is_some() {
+            self.translate_tabs_to_spaces = changes.translate_tabs_to_spaces;
+        }
+        if changes.word_wrap.is_some() {
+            self.word_wrap = changes.word_wrap;
+        }
+        if changes.wrap_width.is_some() {
+            self.wrap_width = changes.wrap_width;
+        }
+    }
+
+    /// Whether xi-core wraps lines, at the view's width or at a column.
+    pub fn wraps(&self) -> bool {
+        self.word_wrap == Some(true) || self.wrap_width.map_or(false, |width| width > 0)
+    }
+}
+
+#[test]
+fn apply_keeps_unchanged_fields() {
+    let mut config = ConfigChanges {
+        font_face: Some("Inconsolata".to_string()),
+        font_size: Some(14.0),
+        ..Default::default()
+    };
+    config.apply(&ConfigChanges {
+        font_size: Some(16.0),
+        ..Default::default()
+    });
+
+    assert_eq!(Some("Inconsolata".to_string()), config.font_face);
+    assert_eq!(Some(16.0), config.font_size);
 }
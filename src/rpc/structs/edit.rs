@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+
+use super::modifyselection::ModifySelection;
+
+/// The `flags` bit xi-core reads from `click` and `drag` to extend the
+/// selection instead of replacing it (shift-click).
+pub const FLAG_SELECT: u64 = 2;
+
+/// The kind of selection a `gesture` edit performs.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GestureType {
+    PointSelect,
+    ToggleSel,
+    RangeSelect,
+    LineSelect,
+    WordSelect,
+    MultiLineSelect,
+    MultiWordSelect,
+}
+
+/// A command of xi's `edit` method family.
+///
+/// Serializes to the `{"method": ..., "params": ...}` pair xi-core expects,
+/// the `view_id` is added by `Client::edit`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum EditCommand {
+    Insert {
+        chars: String,
+    },
+    Paste {
+        chars: String,
+    },
+    InsertNewline,
+    InsertTab,
+    DeleteBackward,
+    DeleteForward,
+    DeleteWordBackward,
+    DeleteWordForward,
+    DeleteToBeginningOfLine,
+    MoveUp,
+    MoveUpAndModifySelection,
+    MoveDown,
+    MoveDownAndModifySelection,
+    MoveLeft,
+    MoveLeftAndModifySelection,
+    MoveRight,
+    MoveRightAndModifySelection,
+    MoveWordLeft,
+    MoveWordLeftAndModifySelection,
+    MoveWordRight,
+    MoveWordRightAndModifySelection,
+    MoveToBeginningOfParagraph,
+    MoveToBeginningOfParagraphAndModifySelection,
+    MoveToEndOfParagraph,
+    MoveToEndOfParagraphAndModifySelection,
+    MoveToLeftEndOfLine,
+    MoveToLeftEndOfLineAndModifySelection,
+    MoveToRightEndOfLine,
+    MoveToRightEndOfLineAndModifySelection,
+    MoveToBeginningOfDocument,
+    MoveToBeginningOfDocumentAndModifySelection,
+    MoveToEndOfDocument,
+    MoveToEndOfDocumentAndModifySelection,
+    ScrollPageUp,
+    PageUpAndModifySelection,
+    ScrollPageDown,
+    PageDownAndModifySelection,
+    SelectAll,
+    AddSelectionAbove,
+    AddSelectionBelow,
+    CollapseSelections,
+    CancelOperation,
+    Undo,
+    Redo,
+    /// The first (inclusive) and last (exclusive) visible lines.
+    Scroll(u64, u64),
+    /// Asks for the first (inclusive) to last (exclusive) lines to be sent
+    /// again, for lines the core left invalid.
+    RequestLines(u64, u64),
+    /// The size of the view in pixels, xi-core wraps to its width.
+    Resize {
+        width: f64,
+        height: f64,
+    },
+    /// Puts the caret at the start of a visual line.
+    GotoLine {
+        line: u64,
+    },
+    /// Line, column, modifier flags and click count.
+    Click(u64, u64, u64, u64),
+    /// Line, column and modifier flags.
+    Drag(u64, u64, u64),
+    Gesture {
+        line: u64,
+        col: u64,
+        ty: GestureType,
+    },
+    Find {
+        chars: String,
+        case_sensitive: bool,
+        regex: bool,
+        whole_words: bool,
+    },
+    FindNext {
+        wrap_around: bool,
+        allow_same: bool,
+        modify_selection: ModifySelection,
+    },
+    FindPrevious {
+        wrap_around: bool,
+        allow_same: bool,
+        modify_selection: ModifySelection,
+    },
+    FindAll,
+    /// Shows or hides the find highlights (style id 1).
+    HighlightFind {
+        visible: bool,
+    },
+    /// Sets the replacement string used by `ReplaceNext` and `ReplaceAll`.
+    Replace {
+        chars: String,
+        preserve_case: bool,
+    },
+    ReplaceNext,
+    ReplaceAll,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn serialize_unit_command() {
+        let value = serde_json::to_value(&EditCommand::MoveUpAndModifySelection).unwrap();
+        assert_eq!(json!({"method": "move_up_and_modify_selection"}), value);
+    }
+
+    #[test]
+    fn serialize_struct_command() {
+        let cmd = EditCommand::Insert {
+            chars: "a".to_string(),
+        };
+        let value = serde_json::to_value(&cmd).unwrap();
+        assert_eq!(json!({"method": "insert", "params": {"chars": "a"}}), value);
+
+        let cmd = EditCommand::Gesture {
+            line: 1,
+            col: 2,
+            ty: GestureType::WordSelect,
+        };
+        let value = serde_json::to_value(&cmd).unwrap();
+        assert_eq!(
+            json!({"method": "gesture", "params": {"line": 1, "col": 2, "ty": "word_select"}}),
+            value
+        );
+
+        let cmd = EditCommand::Resize {
+            width: 640.0,
+            height: 480.0,
+        };
+        let value = serde_json::to_value(&cmd).unwrap();
+        let params = json!({"width": 640.0, "height": 480.0});
+        assert_eq!(json!({"method": "resize", "params": params}), value);
+
+        let value = serde_json::to_value(&EditCommand::GotoLine { line: 7 }).unwrap();
+        assert_eq!(json!({"method": "goto_line", "params": {"line": 7}}), value);
+    }
+
+    #[test]
+    fn serialize_find_command() {
+        let cmd = EditCommand::FindNext {
+            wrap_around: true,
+            allow_same: false,
+            modify_selection: ModifySelection::Set,
+        };
+        let value = serde_json::to_value(&cmd).unwrap();
+        let params = json!({"wrap_around": true, "allow_same": false, "modify_selection": "set"});
+        assert_eq!(json!({"method": "find_next", "params": params}), value);
+
+        let value = serde_json::to_value(&EditCommand::ReplaceAll).unwrap();
+        assert_eq!(json!({"method": "replace_all"}), value);
+    }
+
+    #[test]
+    fn serialize_array_command() {
+        let value = serde_json::to_value(&EditCommand::Scroll(0, 18)).unwrap();
+        assert_eq!(json!({"method": "scroll", "params": [0, 18]}), value);
+
+        let value = serde_json::to_value(&EditCommand::RequestLines(100, 150)).unwrap();
+        assert_eq!(
+            json!({"method": "request_lines", "params": [100, 150]}),
+            value
+        );
+
+        let value = serde_json::to_value(&EditCommand::Click(3, 4, FLAG_SELECT, 1)).unwrap();
+        assert_eq!(json!({"method": "click", "params": [3, 4, 2, 1]}), value);
+    }
+}
@@ -5,7 +5,7 @@
 use super::view::ViewId;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct Query {
     pub id: u64,
     pub chars: Option<String>,
@@ -16,19 +16,19 @@ pub struct Query {
     pub lines: Vec<u64>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct FindStatus {
     pub view_id: ViewId,
     pub queries: Vec<Query>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct Status {
     pub chars: String,
     pub preserve_case: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct ReplaceStatus {
     pub view_id: ViewId,
     pub status: Status,
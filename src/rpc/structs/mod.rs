@@ -3,7 +3,9 @@
 // SPDX-License-Identifier: MIT
 
 mod alert;
+mod annotation;
 mod config;
+mod edit;
 mod findreplace;
 mod language;
 mod line;
@@ -18,8 +20,10 @@ mod update;
 mod view;
 
 pub use self::alert::Alert;
+pub use self::annotation::{Annotation, AnnotationType};
 pub use self::config::ConfigChanged;
 pub use self::config::ConfigChanges;
+pub use self::edit::{EditCommand, GestureType, FLAG_SELECT};
 pub use self::findreplace::{FindStatus, Query, ReplaceStatus, Status};
 pub use self::language::{AvailableLanguages, LanguageChanged};
 pub use self::line::{Line, StyleDef};
@@ -27,6 +31,8 @@ pub use self::modifyselection::ModifySelection;
 pub use self::operation::{Operation, OperationType};
 pub use self::plugins::AvailablePlugins;
 pub use self::plugins::Plugin;
+pub use self::plugins::PluginCommand;
+pub use self::plugins::PluginRpc;
 pub use self::plugins::PluginStarted;
 pub use self::plugins::PluginStopped;
 pub use self::plugins::UpdateCmds;
@@ -35,25 +41,4 @@ pub use self::scroll_to::ScrollTo;
 pub use self::style::Style;
 pub use self::theme::{AvailableThemes, ThemeChanged, ThemeSettings};
 pub use self::update::Update;
-pub use self::view::{MeasureWidth, ViewId};
-
-/// Represents all possible RPC messages recieved from xi-core.
-#[derive(Debug)]
-pub enum RpcOperations {
-    Update(Update),
-    ScrollTo(ScrollTo),
-    DefStyle(Style),
-    AvailablePlugins(AvailablePlugins),
-    UpdateCmds(UpdateCmds),
-    PluginStarted(PluginStarted),
-    PluginStopped(PluginStopped),
-    ConfigChanged(ConfigChanged),
-    ThemeChanged(ThemeChanged),
-    Alert(Alert),
-    AvailableThemes(AvailableThemes),
-    FindStatus(FindStatus),
-    ReplaceStatus(ReplaceStatus),
-    AvailableLanguages(AvailableLanguages),
-    LanguageChanged(LanguageChanged),
-    MeasureWidth((u64, MeasureWidth)),
-}
+pub use self::view::{MeasureWidth, MeasureWidthInner, ViewId};
@@ -4,6 +4,7 @@
 
 use crate::ViewId;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct Plugin {
@@ -31,7 +32,79 @@ pub struct PluginStopped {
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct UpdateCmds {
-    pub cmds: Vec<String>,
+    pub cmds: Vec<PluginCommand>,
     pub plugin: String,
     pub view_id: ViewId,
 }
+
+/// A command a plugin advertises through `update_cmds`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct PluginCommand {
+    pub title: String,
+    pub description: String,
+    /// What to send the plugin to run the command.
+    pub rpc_cmd: PluginRpc,
+    #[serde(default)]
+    pub args: Vec<CommandArgument>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RpcType {
+    Notification,
+    Request,
+}
+
+/// The `rpc` of a `plugin_rpc`, with `params` holding the argument values.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct PluginRpc {
+    pub rpc_type: RpcType,
+    pub method: String,
+    pub params: Value,
+}
+
+/// An argument of a `PluginCommand`, filled into `params[key]`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct CommandArgument {
+    pub title: String,
+    pub description: String,
+    pub key: String,
+    /// `Number`, `Int`, `PosInt`, `Bool`, `String` or `Choice`.
+    pub arg_type: String,
+    /// The values to pick from, for a `Choice`.
+    #[serde(default)]
+    pub options: Vec<ArgumentOption>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ArgumentOption {
+    pub title: String,
+    pub value: Value,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_update_cmds() {
+        // as sent by xi-core for its test plugin
+        let s = r#"{"cmds": [{"title": "Test Command", "description": "Passes the current test", "rpc_cmd": {"rpc_type": "notification", "method": "test.cmd", "params": {"view": "", "non_arg": "plugin supplied value", "arg_one": "", "arg_two": ""}}, "args": [{"title": "First argument", "description": "Indicates something", "key": "arg_one", "arg_type": "Bool"}, {"title": "Favourite Number", "description": "A number used in a test.", "key": "arg_two", "arg_type": "Choice", "options": [{"title": "Five", "value": 5}, {"title": "Ten", "value": 10}]}]}], "plugin": "test-plugin", "view_id": "view-id-1"}"#;
+        let update: UpdateCmds = serde_json::from_str(s).unwrap();
+
+        assert_eq!("test-plugin", update.plugin);
+        assert_eq!(1, update.cmds.len());
+        let cmd = &update.cmds[0];
+        assert_eq!("Test Command", cmd.title);
+        assert_eq!(RpcType::Notification, cmd.rpc_cmd.rpc_type);
+        assert_eq!("test.cmd", cmd.rpc_cmd.method);
+        assert_eq!(
+            json!("plugin supplied value"),
+            cmd.rpc_cmd.params["non_arg"]
+        );
+        assert_eq!(2, cmd.args.len());
+        assert!(cmd.args[0].options.is_empty());
+        assert_eq!(json!(10), cmd.args[1].options[1].value);
+    }
+}
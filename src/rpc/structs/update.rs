@@ -4,6 +4,7 @@
 
 use serde::{Deserialize, Deserializer};
 
+use crate::Annotation;
 use crate::Operation;
 use crate::ViewId;
 
@@ -13,6 +14,9 @@ pub struct Update {
     pub operations: Vec<Operation>,
     pub pristine: bool,
     pub view_id: ViewId,
+    /// Sent by newer xi-core only, which then draws selections and find
+    /// results from them rather than from styles 0 and 1.
+    pub annotations: Option<Vec<Annotation>>,
 }
 
 #[derive(Deserialize, Debug, PartialEq)]
@@ -21,6 +25,8 @@ struct InnerUpdate {
     #[serde(rename = "ops")]
     pub operations: Vec<Operation>,
     pub pristine: bool,
+    #[serde(default)]
+    pub annotations: Option<Vec<Annotation>>,
 }
 
 #[derive(Deserialize, Debug, PartialEq)]
@@ -39,6 +45,7 @@ impl<'de> Deserialize<'de> for Update {
             operations: update.operations,
             pristine: update.pristine,
             view_id,
+            annotations: update.annotations,
         })
     }
 }
@@ -83,6 +90,7 @@ fn deserialize_update() {
         pristine: true,
         rev: None,
         view_id: FromStr::from_str("view-id-1").unwrap(),
+        annotations: None,
     };
     let deserialized: Result<Update, _> = serde_json::from_str(s);
     assert_eq!(deserialized.unwrap(), update);
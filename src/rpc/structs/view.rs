@@ -2,6 +2,7 @@
 // Copyright (c) 2017 Corentin Henry
 // SPDX-License-Identifier: MIT
 
+use druid::Data;
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
@@ -72,6 +73,12 @@ impl fmt::Display for ViewId {
     }
 }
 
+impl Data for ViewId {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
 impl Serialize for ViewId {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -0,0 +1,168 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+
+use log::*;
+use pipe::pipe;
+use serde::{Deserialize, Serialize};
+use xi_core_lib::XiCore;
+use xi_rpc::RpcLoop;
+
+use crate::rpc::recorder::ReplayTransport;
+
+pub type XiReader = Box<dyn BufRead + Send>;
+pub type XiWriter = Box<dyn Write + Send>;
+
+/// A connection to xi-core: line-delimited JSON-RPC in both directions.
+pub trait Transport {
+    /// Starts or connects to the core, returning what it writes to us and
+    /// where we write to it.
+    fn connect(self: Box<Self>) -> io::Result<(XiReader, XiWriter)>;
+}
+
+/// Runs `XiCore` on a thread of this process, talking over in-memory pipes.
+pub struct InProcessTransport;
+
+impl InProcessTransport {
+    pub fn start() -> (XiReader, XiWriter) {
+        let (to_core_rx, to_core_tx) = pipe();
+        let (from_core_rx, from_core_tx) = pipe();
+        let mut state = XiCore::new();
+        let mut rpc_looper = RpcLoop::new(from_core_tx);
+        thread::spawn(move || rpc_looper.mainloop(|| to_core_rx, &mut state));
+        (Box::new(from_core_rx), Box::new(to_core_tx))
+    }
+}
+
+impl Transport for InProcessTransport {
+    fn connect(self: Box<Self>) -> io::Result<(XiReader, XiWriter)> {
+        Ok(InProcessTransport::start())
+    }
+}
+
+/// Spawns an external `xi-core` binary and talks to it over stdio, so a
+/// crash of the core doesn't take the UI down with it.
+pub struct ChildProcessTransport {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+}
+
+impl Transport for ChildProcessTransport {
+    fn connect(self: Box<Self>) -> io::Result<(XiReader, XiWriter)> {
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()?;
+
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+
+        let program = self.program.clone();
+        thread::spawn(move || match child.wait() {
+            Ok(status) => info!("xi-core {:?} exited: {}", program, status),
+            Err(err) => error!("xi-core {:?} wait error: {}", program, err),
+        });
+
+        Ok((Box::new(BufReader::new(stdout)), Box::new(stdin)))
+    }
+}
+
+/// Connects to a core that is already listening on a Unix-domain socket.
+pub struct UnixSocketTransport {
+    pub path: PathBuf,
+}
+
+impl Transport for UnixSocketTransport {
+    #[cfg(unix)]
+    fn connect(self: Box<Self>) -> io::Result<(XiReader, XiWriter)> {
+        use std::os::unix::net::UnixStream;
+
+        let stream = UnixStream::connect(&self.path)?;
+        let reader = stream.try_clone()?;
+        Ok((Box::new(BufReader::new(reader)), Box::new(stream)))
+    }
+
+    #[cfg(not(unix))]
+    fn connect(self: Box<Self>) -> io::Result<(XiReader, XiWriter)> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "unix sockets are not supported on this platform",
+        ))
+    }
+}
+
+/// Which `Transport` to use, as stored in the config file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TransportConfig {
+    InProcess,
+    ChildProcess {
+        path: PathBuf,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    UnixSocket {
+        path: PathBuf,
+    },
+    /// Plays back a recording made with `record_rpc` instead of running a core.
+    Replay {
+        path: PathBuf,
+    },
+}
+
+impl Default for TransportConfig {
+    fn default() -> Self {
+        TransportConfig::InProcess
+    }
+}
+
+impl TransportConfig {
+    pub fn transport(&self) -> Box<dyn Transport> {
+        match self {
+            TransportConfig::InProcess => Box::new(InProcessTransport),
+            TransportConfig::ChildProcess { path, args } => Box::new(ChildProcessTransport {
+                program: path.clone(),
+                args: args.clone(),
+            }),
+            TransportConfig::UnixSocket { path } => {
+                Box::new(UnixSocketTransport { path: path.clone() })
+            }
+            TransportConfig::Replay { path } => Box::new(ReplayTransport { path: path.clone() }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn deserialize_transport_config() {
+        let config: TransportConfig =
+            serde_json::from_value(json!({"type": "in_process"})).unwrap();
+        assert_eq!(TransportConfig::InProcess, config);
+
+        let value = json!({"type": "child_process", "path": "/usr/local/bin/xi-core"});
+        let config: TransportConfig = serde_json::from_value(value).unwrap();
+        assert_eq!(
+            TransportConfig::ChildProcess {
+                path: PathBuf::from("/usr/local/bin/xi-core"),
+                args: vec![],
+            },
+            config
+        );
+
+        let value = json!({"type": "unix_socket", "path": "/tmp/xi.sock"});
+        let config: TransportConfig = serde_json::from_value(value).unwrap();
+        assert_eq!(
+            TransportConfig::UnixSocket {
+                path: PathBuf::from("/tmp/xi.sock"),
+            },
+            config
+        );
+    }
+}
@@ -1,30 +1,18 @@
 extern crate dirs;
 
 use crate::app_state::AppState;
+use crate::model::notification::Level;
 use std::fs;
-use std::fs::File;
+use std::io;
 use std::path::PathBuf;
 
-pub fn save_config(state: &AppState) {
-    let result = serde_json::to_string_pretty(&state);
-    match result {
-        Ok(str) => {
-            let path = config_path().expect("lost home issue");
-            if !path.exists() {
-                let _file = File::create(&path).unwrap();
-            }
-
-            let result = fs::write(&path, str);
-
-            match result {
-                Ok(_) => log::info!("save config: {:?}", path),
-                Err(e) => log::info!("failed to write data: {}", { e }),
-            }
-        }
-        Err(err) => {
-            log::info!("serialize config error: {:?}", err);
-        }
-    }
+pub fn save_config(state: &AppState) -> io::Result<()> {
+    let str = serde_json::to_string_pretty(&state)?;
+    let path = config_path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no home directory"))?;
+    fs::write(&path, str)?;
+    log::info!("save config: {:?}", path);
+    Ok(())
 }
 
 #[allow(unused_assignments)]
@@ -45,19 +33,29 @@ pub fn read_config() -> AppState {
         Ok(state) => {
             app_state = state;
         }
-        Err(_err) => {
+        Err(err) => {
             log::error!("error config: {}", content);
+            let message = format!("Failed to load {}: {}", path.display(), err);
+            app_state.notify(Level::Error, message);
         }
     };
     return app_state;
 }
 
 pub fn config_path() -> Option<PathBuf> {
+    let config_path = base_dir()?.join("print.json");
+    Some(config_path)
+}
+
+pub fn recordings_dir() -> Option<PathBuf> {
+    Some(base_dir()?.join("recordings"))
+}
+
+fn base_dir() -> Option<PathBuf> {
     let home = dirs::home_dir()?;
     let base = home.join(".print");
     if !&base.exists() {
         let _ = fs::create_dir_all(&base);
     }
-    let config_path = base.join("print.json");
-    Some(config_path)
+    Some(base)
 }
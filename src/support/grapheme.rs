@@ -0,0 +1,45 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Moves a byte offset back to the start of the grapheme cluster it falls
+/// in, so a caret never lands inside a character or between a letter and
+/// its combining marks. Offsets past the end snap to the end.
+pub fn snap_to_grapheme(text: &str, offset: usize) -> usize {
+    if offset >= text.len() {
+        return text.len();
+    }
+    text.grapheme_indices(true)
+        .map(|(index, _)| index)
+        .take_while(|index| *index <= offset)
+        .last()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offsets_inside_a_char_snap_back() {
+        // "é" is two bytes
+        let text = "aé b";
+        assert_eq!(1, snap_to_grapheme(text, 1));
+        assert_eq!(1, snap_to_grapheme(text, 2));
+        assert_eq!(3, snap_to_grapheme(text, 3));
+        assert_eq!(5, snap_to_grapheme(text, 9));
+    }
+
+    #[test]
+    fn combining_marks_and_emoji_stay_whole() {
+        // "e" followed by a combining acute accent
+        let text = "e\u{301}x";
+        assert_eq!(0, snap_to_grapheme(text, 1));
+        assert_eq!(3, snap_to_grapheme(text, 3));
+
+        // a family emoji joined with ZWJs
+        let text = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}!";
+        let end = text.len() - 1;
+        assert_eq!(0, snap_to_grapheme(text, 4));
+        assert_eq!(0, snap_to_grapheme(text, end - 1));
+        assert_eq!(end, snap_to_grapheme(text, end));
+    }
+}
@@ -0,0 +1,399 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// How a language is recognized, and what xi-core (syntect) may call it.
+struct LanguageDef {
+    /// Candidate names for `set_language`, the first one the core has wins.
+    names: &'static [&'static str],
+    extensions: &'static [&'static str],
+    file_names: &'static [&'static str],
+    interpreters: &'static [&'static str],
+}
+
+const LANGUAGES: &[LanguageDef] = &[
+    LanguageDef {
+        names: &["Rust"],
+        extensions: &["rs"],
+        file_names: &[],
+        interpreters: &[],
+    },
+    LanguageDef {
+        names: &["JavaScript", "JavaScript (Babel)"],
+        extensions: &["js", "mjs", "cjs", "jsx"],
+        file_names: &[],
+        interpreters: &["node", "nodejs"],
+    },
+    LanguageDef {
+        names: &["TypeScript"],
+        extensions: &["ts", "tsx"],
+        file_names: &[],
+        interpreters: &["ts-node", "deno"],
+    },
+    LanguageDef {
+        names: &["Python"],
+        extensions: &["py", "pyw", "pyi"],
+        file_names: &["SConstruct", "SConscript"],
+        interpreters: &["python", "python2", "python3"],
+    },
+    LanguageDef {
+        names: &["Ruby"],
+        extensions: &["rb", "rake", "gemspec"],
+        file_names: &["Gemfile", "Rakefile"],
+        interpreters: &["ruby"],
+    },
+    LanguageDef {
+        names: &["Bourne Again Shell (bash)", "Shell-Unix-Generic", "Shell"],
+        extensions: &["sh", "bash", "zsh"],
+        file_names: &[".bashrc", ".bash_profile", ".zshrc", ".profile"],
+        interpreters: &["sh", "bash", "zsh", "dash", "ksh"],
+    },
+    LanguageDef {
+        names: &["Perl"],
+        extensions: &["pl", "pm", "t"],
+        file_names: &[],
+        interpreters: &["perl"],
+    },
+    LanguageDef {
+        names: &["Lua"],
+        extensions: &["lua"],
+        file_names: &[],
+        interpreters: &["lua"],
+    },
+    LanguageDef {
+        names: &["PHP"],
+        extensions: &["php"],
+        file_names: &[],
+        interpreters: &["php"],
+    },
+    LanguageDef {
+        names: &["Go"],
+        extensions: &["go"],
+        file_names: &[],
+        interpreters: &[],
+    },
+    LanguageDef {
+        names: &["C"],
+        extensions: &["c", "h"],
+        file_names: &[],
+        interpreters: &[],
+    },
+    LanguageDef {
+        names: &["C++"],
+        extensions: &["cpp", "cc", "cxx", "hpp", "hh", "hxx"],
+        file_names: &[],
+        interpreters: &[],
+    },
+    LanguageDef {
+        names: &["Java"],
+        extensions: &["java"],
+        file_names: &[],
+        interpreters: &[],
+    },
+    LanguageDef {
+        names: &["Markdown"],
+        extensions: &["md", "markdown"],
+        file_names: &[],
+        interpreters: &[],
+    },
+    LanguageDef {
+        names: &["JSON"],
+        extensions: &["json"],
+        file_names: &[],
+        interpreters: &[],
+    },
+    LanguageDef {
+        names: &["YAML"],
+        extensions: &["yaml", "yml"],
+        file_names: &[],
+        interpreters: &[],
+    },
+    LanguageDef {
+        names: &["TOML"],
+        extensions: &["toml"],
+        file_names: &["Cargo.lock"],
+        interpreters: &[],
+    },
+    LanguageDef {
+        names: &["HTML"],
+        extensions: &["html", "htm"],
+        file_names: &[],
+        interpreters: &[],
+    },
+    LanguageDef {
+        names: &["CSS"],
+        extensions: &["css"],
+        file_names: &[],
+        interpreters: &[],
+    },
+    LanguageDef {
+        names: &["XML"],
+        extensions: &["xml", "svg"],
+        file_names: &[],
+        interpreters: &[],
+    },
+    LanguageDef {
+        names: &["SQL"],
+        extensions: &["sql"],
+        file_names: &[],
+        interpreters: &[],
+    },
+    LanguageDef {
+        names: &["Makefile"],
+        extensions: &["mk"],
+        file_names: &["Makefile", "makefile", "GNUmakefile"],
+        interpreters: &["make"],
+    },
+    LanguageDef {
+        names: &["Plain Text"],
+        extensions: &["txt"],
+        file_names: &[],
+        interpreters: &[],
+    },
+];
+
+/// Vim and Emacs look for modelines in this many lines at either end.
+const MODELINE_LINES: usize = 5;
+
+/// Modelines and shebangs sit at either end of a file, so `read_ends` reads
+/// at most this many bytes from each end.
+const SNIFF_BYTES: u64 = 4096;
+
+/// The start and the end of the file at `path`, enough text for `detect`
+/// without reading a large file in full.
+pub fn read_ends(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let mut bytes = Vec::new();
+    if len <= 2 * SNIFF_BYTES {
+        file.read_to_end(&mut bytes)?;
+        return Ok(String::from_utf8_lossy(&bytes).into_owned());
+    }
+
+    (&mut file).take(SNIFF_BYTES).read_to_end(&mut bytes)?;
+    let mut text = String::from_utf8_lossy(&bytes).into_owned();
+    text.push('\n');
+
+    bytes.clear();
+    file.seek(SeekFrom::End(-(SNIFF_BYTES as i64)))?;
+    file.read_to_end(&mut bytes)?;
+    text.push_str(&String::from_utf8_lossy(&bytes));
+    Ok(text)
+}
+
+/// Picks the language of a file from, in order of precedence, a modeline,
+/// a shebang and the file name. Only returns names from `available`, the
+/// list xi-core sent with `available_languages`.
+pub fn detect(path: &Path, text: &str, available: &[String]) -> Option<String> {
+    if let Some(mode) = modeline(text) {
+        let found = resolve_mode(&mode, available);
+        if found.is_some() {
+            return found;
+        }
+    }
+
+    if let Some(interpreter) = shebang(text) {
+        let def = LANGUAGES
+            .iter()
+            .find(|def| def.interpreters.contains(&interpreter.as_str()));
+        if let Some(found) = def.and_then(|def| resolve(def, available)) {
+            return Some(found);
+        }
+    }
+
+    let file_name = path.file_name().and_then(|name| name.to_str())?;
+    if let Some(def) = LANGUAGES
+        .iter()
+        .find(|def| def.file_names.contains(&file_name))
+    {
+        return resolve(def, available);
+    }
+
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())?
+        .to_lowercase();
+    let def = LANGUAGES
+        .iter()
+        .find(|def| def.extensions.contains(&extension.as_str()))?;
+    resolve(def, available)
+}
+
+fn resolve(def: &LanguageDef, available: &[String]) -> Option<String> {
+    def.names
+        .iter()
+        .find_map(|name| find_available(name, available))
+}
+
+fn find_available(name: &str, available: &[String]) -> Option<String> {
+    available
+        .iter()
+        .find(|language| language.eq_ignore_ascii_case(name))
+        .cloned()
+}
+
+/// A modeline names a language loosely, e.g. `ft=javascript` or `mode: js`.
+fn resolve_mode(mode: &str, available: &[String]) -> Option<String> {
+    let mode = mode.to_lowercase();
+    let def = LANGUAGES.iter().find(|def| {
+        def.names
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(&mode))
+            || def.extensions.contains(&mode.as_str())
+            || def.interpreters.contains(&mode.as_str())
+    });
+    match def {
+        Some(def) => resolve(def, available),
+        None => find_available(&mode, available),
+    }
+}
+
+/// The interpreter of a `#!` line, skipping `env` and its flags.
+fn shebang(text: &str) -> Option<String> {
+    let line = text.lines().next()?.strip_prefix("#!")?;
+    let mut words = line.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-'))?;
+    }
+    Some(program.to_string())
+}
+
+/// The file type of a Vim (`vim: set ft=rust:`) or Emacs (`-*- mode: rust -*-`)
+/// modeline in the first or last lines.
+fn modeline(text: &str) -> Option<String> {
+    let lines: Vec<&str> = text.lines().collect();
+    let tail = lines
+        .len()
+        .saturating_sub(MODELINE_LINES)
+        .max(MODELINE_LINES);
+    lines
+        .iter()
+        .take(MODELINE_LINES)
+        .chain(lines.iter().skip(tail))
+        .find_map(|line| emacs_mode(line).or_else(|| vim_filetype(line)))
+}
+
+fn emacs_mode(line: &str) -> Option<String> {
+    let start = line.find("-*-")? + 3;
+    let end = start + line[start..].find("-*-")?;
+    let vars = line[start..end].trim();
+    if !vars.contains(':') {
+        // the short form, `-*- rust -*-`
+        return Some(vars.to_string()).filter(|mode| !mode.is_empty());
+    }
+    vars.split(';').find_map(|var| {
+        let (key, value) = var.split_once(':')?;
+        if key.trim().eq_ignore_ascii_case("mode") {
+            Some(value.trim().to_string())
+        } else {
+            None
+        }
+    })
+}
+
+fn vim_filetype(line: &str) -> Option<String> {
+    let start = ["vim:", "vi:", "ex:"]
+        .iter()
+        .filter_map(|marker| line.find(marker).map(|index| index + marker.len()))
+        .min()?;
+    line[start..]
+        .split(|c: char| c.is_whitespace() || c == ':')
+        .find_map(|option| {
+            let value = option
+                .strip_prefix("ft=")
+                .or_else(|| option.strip_prefix("filetype="))
+                .or_else(|| option.strip_prefix("syntax="))
+                .or_else(|| option.strip_prefix("syn="))?;
+            Some(value.to_string()).filter(|value| !value.is_empty())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn available() -> Vec<String> {
+        [
+            "Rust",
+            "JavaScript",
+            "Python",
+            "Bourne Again Shell (bash)",
+            "Plain Text",
+        ]
+        .iter()
+        .map(|name| name.to_string())
+        .collect()
+    }
+
+    #[test]
+    fn detect_by_extension() {
+        let available = available();
+        let detect = |path: &str| detect(Path::new(path), "", &available);
+        assert_eq!(Some("Rust".to_string()), detect("src/main.rs"));
+        assert_eq!(Some("JavaScript".to_string()), detect("app.MJS"));
+        assert_eq!(
+            Some("Bourne Again Shell (bash)".to_string()),
+            detect("/home/me/.bashrc")
+        );
+        assert_eq!(None, detect("README.md"));
+        assert_eq!(None, detect("LICENSE"));
+    }
+
+    #[test]
+    fn detect_by_shebang() {
+        let available = available();
+        let path = Path::new("bin/tool");
+        let detect = |text: &str| detect(path, text, &available);
+        assert_eq!(
+            Some("Python".to_string()),
+            detect("#!/usr/bin/env python3\n")
+        );
+        assert_eq!(
+            Some("JavaScript".to_string()),
+            detect("#!/usr/bin/env -S node --harmony\n")
+        );
+        assert_eq!(
+            Some("Bourne Again Shell (bash)".to_string()),
+            detect("#!/bin/sh\n")
+        );
+        assert_eq!(None, detect("#!/usr/bin/awk -f\n"));
+    }
+
+    #[test]
+    fn modeline_wins() {
+        let available = available();
+        let path = Path::new("config.txt");
+        let detect = |text: &str| detect(path, text, &available);
+        assert_eq!(
+            Some("Python".to_string()),
+            detect("# -*- mode: python; coding: utf-8 -*-\n")
+        );
+        assert_eq!(Some("JavaScript".to_string()), detect("// -*- js -*-\n"));
+
+        let text = format!("{}// vim: set ts=4 ft=rust:\n", "x\n".repeat(20));
+        assert_eq!(Some("Rust".to_string()), detect(&text));
+
+        let text = format!("// vim: ft=rust\n{}", "x\n".repeat(20));
+        assert_eq!(Some("Rust".to_string()), detect(&text));
+
+        // a modeline in the middle of the file doesn't count
+        let text = format!("{}// vim: ft=rust\n{}", "x\n".repeat(20), "x\n".repeat(20));
+        assert_eq!(Some("Plain Text".to_string()), detect(&text));
+    }
+
+    #[test]
+    fn large_files_are_read_at_the_ends_only() {
+        let path = std::env::temp_dir().join(format!("print-language-{}", std::process::id()));
+        let middle = "x\n".repeat(SNIFF_BYTES as usize * 4);
+        let text = format!("#!/usr/bin/env python3\n{}// vim: ft=rust\n", middle);
+        std::fs::write(&path, &text).unwrap();
+
+        let ends = read_ends(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(ends.len() <= 2 * SNIFF_BYTES as usize + 1);
+        assert!(ends.starts_with("#!/usr/bin/env python3\n"));
+        assert!(ends.ends_with("// vim: ft=rust\n"));
+        assert_eq!(Some("Rust".to_string()), detect(&path, &ends, &available()));
+    }
+}
@@ -1,2 +1,6 @@
 pub mod directory;
+pub mod grapheme;
+pub mod language;
 pub mod line;
+pub mod tabs;
+pub mod text_measure;
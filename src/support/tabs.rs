@@ -0,0 +1,106 @@
+/// A line with its tabs expanded to spaces, so piet lays it out on the
+/// same tab stops as xi-core counts columns.
+pub struct ExpandedLine {
+    pub text: String,
+    /// The byte offset in `text` of every byte offset of the original line,
+    /// plus one past the end.
+    offsets: Vec<usize>,
+}
+
+impl ExpandedLine {
+    pub fn new(line: &str, tab_size: usize) -> ExpandedLine {
+        let tab_size = tab_size.max(1);
+        let mut text = String::with_capacity(line.len());
+        let mut offsets = Vec::with_capacity(line.len() + 1);
+        let mut column = 0;
+
+        for c in line.chars() {
+            offsets.extend(std::iter::repeat(text.len()).take(c.len_utf8()));
+            if c == '\t' {
+                let spaces = tab_size - column % tab_size;
+                text.extend(std::iter::repeat(' ').take(spaces));
+                column += spaces;
+            } else {
+                text.push(c);
+                column += 1;
+            }
+        }
+        offsets.push(text.len());
+
+        ExpandedLine { text, offsets }
+    }
+
+    /// Maps a byte offset of the original line into `text`, clamping offsets
+    /// past the end.
+    pub fn offset(&self, offset: usize) -> usize {
+        self.offsets[offset.min(self.offsets.len() - 1)]
+    }
+
+    /// Maps a byte offset of `text` back onto the original line, to the
+    /// nearest char boundary. Offsets inside an expanded tab go to whichever
+    /// side of the tab is closer.
+    pub fn original(&self, expanded: usize) -> usize {
+        let next = self.offsets.partition_point(|offset| *offset < expanded);
+        if next == self.offsets.len() {
+            return next - 1;
+        }
+        if next == 0 || self.offsets[next] == expanded {
+            return next;
+        }
+        let before = self.offsets[next - 1];
+        if expanded - before < self.offsets[next] - expanded {
+            self.offsets.partition_point(|offset| *offset < before)
+        } else {
+            next
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tabs_stop_at_multiples_of_tab_size() {
+        let line = ExpandedLine::new("\ta\tbc\td", 4);
+        assert_eq!("    a   bc  d", line.text);
+        assert_eq!(4, line.offset(1));
+        assert_eq!(8, line.offset(3));
+        assert_eq!(12, line.offset(6));
+        assert_eq!(13, line.offset(7));
+        assert_eq!(13, line.offset(100));
+    }
+
+    #[test]
+    fn expanded_offsets_map_back() {
+        let line = ExpandedLine::new("\ta\tbc\td", 4);
+        assert_eq!(0, line.original(0));
+        assert_eq!(0, line.original(1));
+        assert_eq!(1, line.original(3));
+        assert_eq!(1, line.original(4));
+        assert_eq!(5, line.original(10));
+        assert_eq!(6, line.original(11));
+        assert_eq!(7, line.original(13));
+        assert_eq!(7, line.original(100));
+
+        let line = ExpandedLine::new("é\tx", 4);
+        assert_eq!(2, line.original(2));
+        assert_eq!(3, line.original(5));
+    }
+
+    #[test]
+    fn offsets_follow_multibyte_chars() {
+        let line = ExpandedLine::new("é\tx", 4);
+        assert_eq!("é   x", line.text);
+        assert_eq!(0, line.offset(0));
+        assert_eq!(2, line.offset(2));
+        assert_eq!(5, line.offset(3));
+    }
+
+    #[test]
+    fn line_without_tabs_is_unchanged() {
+        let line = ExpandedLine::new("fn main() {}", 4);
+        assert_eq!("fn main() {}", line.text);
+        assert_eq!(3, line.offset(3));
+    }
+}
@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use piet_common::{
+    Device, FontFamily, FontStyle, FontWeight, RenderContext, Text, TextAttribute, TextLayout,
+    TextLayoutBuilder,
+};
+
+use crate::rpc::structs::MeasureWidthInner;
+use crate::{ConfigChanges, MeasureWidth, Style};
+
+/// What xi-core uses until the user config says otherwise.
+pub const DEFAULT_FONT_SIZE: f64 = 14.0;
+
+/// Answers xi-core's `measure_width` requests with piet's text layout,
+/// using the font from the latest `config_changed`.
+pub struct TextMeasure {
+    font_face: Option<String>,
+    font_size: f64,
+}
+
+impl TextMeasure {
+    pub fn new(config: &ConfigChanges) -> TextMeasure {
+        TextMeasure {
+            font_face: config.font_face.clone(),
+            font_size: config.font_size.map(f64::from).unwrap_or(DEFAULT_FONT_SIZE),
+        }
+    }
+
+    /// Returns one list of widths per batch, in the order xi-core sent them.
+    pub fn measure(
+        &self,
+        request: &MeasureWidth,
+        styles: &HashMap<usize, Style>,
+    ) -> Result<Vec<Vec<f64>>, piet_common::Error> {
+        let mut device = Device::new()?;
+        let mut target = device.bitmap_target(1, 1, 1.0)?;
+        let mut rc = target.render_context();
+
+        let widths = {
+            let text = rc.text();
+            let family = self
+                .font_face
+                .as_ref()
+                .and_then(|face| text.font_family(face))
+                .unwrap_or(FontFamily::MONOSPACE);
+
+            let mut widths = Vec::with_capacity(request.0.len());
+            for batch in request.0.iter() {
+                let style = styles.get(&(batch.id as usize));
+                widths.push(self.measure_batch(text, &family, batch, style)?);
+            }
+            widths
+        };
+
+        rc.finish()?;
+        Ok(widths)
+    }
+
+    fn measure_batch<T: Text>(
+        &self,
+        text: &mut T,
+        family: &FontFamily,
+        batch: &MeasureWidthInner,
+        style: Option<&Style>,
+    ) -> Result<Vec<f64>, piet_common::Error> {
+        let mut widths = Vec::with_capacity(batch.strings.len());
+        for string in batch.strings.iter() {
+            let mut builder = text
+                .new_text_layout(string.clone())
+                .font(family.clone(), self.font_size);
+
+            if let Some(weight) = style.and_then(|s| s.weight) {
+                let attr = TextAttribute::Weight(FontWeight::new(weight as u16));
+                builder = builder.default_attribute(attr);
+            }
+            if let Some(true) = style.and_then(|s| s.italic) {
+                builder = builder.default_attribute(TextAttribute::Style(FontStyle::Italic));
+            }
+
+            // xi-core needs the full advance, including trailing spaces, to wrap words.
+            widths.push(builder.build()?.trailing_whitespace_width());
+        }
+        Ok(widths)
+    }
+}